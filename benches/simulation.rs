@@ -0,0 +1,104 @@
+// Micro-benchmarks of the hot paths, run with `cargo bench`. Each case is repeated for
+// about a second and reported as the mean time per iteration
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use simple_life::being::{Being, BeingType};
+use simple_life::config::Config;
+use simple_life::food::Food;
+use simple_life::simulation::{Simulation, FOOD_GRID_CELL_SIZE};
+use simple_life::spatial::{QuadTree, SpatialGrid};
+
+const MEASURE_TIME: Duration = Duration::from_secs(1);
+const SEED: u64 = 0;
+
+fn bench(name: &str, mut f: impl FnMut()) {
+    f();  // Warm-up
+    let start = Instant::now();
+    let mut iterations = 0u32;
+    while start.elapsed() < MEASURE_TIME {
+        f();
+        iterations += 1;
+    }
+    let per_iteration = start.elapsed() / iterations;
+    println!("{:<40} {:>12.3?} per iteration ({} iterations)", name, per_iteration, iterations);
+}
+
+// A full food layer and one query point per being, as in a busy tick
+fn food_scene(config: &Config) -> (Vec<Food>, Vec<(f64, f64)>) {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let foods = (0..config.max_food).filter_map(|_| Food::new(config, &mut rng)).collect();
+    let queries = (0..config.max_beings)
+        .map(|_| (rng.random_range(0.0..config.world_width), rng.random_range(0.0..config.world_height)))
+        .collect();
+    (foods, queries)
+}
+
+fn nearest_food(config: &Config) {
+    let (foods, queries) = food_scene(config);
+    let range = 25.0;
+
+    bench("nearest food, linear scan", || {
+        for &(x, y) in &queries {
+            let nearest = foods.iter()
+                .enumerate()
+                .map(|(idx, f)| (idx, (f.x - x).powi(2) + (f.y - y).powi(2)))
+                .filter(|&(_, dist_sq)| dist_sq < range * range)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            black_box(nearest);
+        }
+    });
+
+    bench("nearest food, grid (incl. build)", || {
+        let grid = SpatialGrid::from_entities(&foods, FOOD_GRID_CELL_SIZE, config.world_width, config.world_height, false);
+        for &(x, y) in &queries {
+            black_box(grid.nearest_within(x, y, range, |_| true));
+        }
+    });
+}
+
+// Every being looking for others within a perception radius, as predators do
+fn range_queries(config: &Config, population: usize) {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let beings: Vec<Being> = (0..population)
+        .map(|_| {
+            let (x, y) = (rng.random_range(0.0..config.world_width), rng.random_range(0.0..config.world_height));
+            Being::new(x, y, BeingType::Carnivore, config, &mut rng)
+        })
+        .collect();
+    let radius = 50.0;
+
+    bench(&format!("range query x{}, brute force", population), || {
+        for hunter in &beings {
+            let in_range = beings.iter()
+                .filter(|b| (b.x - hunter.x).powi(2) + (b.y - hunter.y).powi(2) <= radius * radius)
+                .count();
+            black_box(in_range);
+        }
+    });
+
+    bench(&format!("range query x{}, quadtree (incl. build)", population), || {
+        let tree = QuadTree::build(&beings, None);
+        for hunter in &beings {
+            black_box(tree.query_range((hunter.x, hunter.y), radius).len());
+        }
+    });
+}
+
+// Headless ticks at the default caps, the world keeps evolving between iterations
+fn ticks(config: &Config) {
+    let mut sim = Simulation::new(config.clone(), SEED);
+    bench("tick, default config", || {
+        black_box(sim.tick());
+    });
+}
+
+fn main() {
+    let config = Config::default();
+    nearest_food(&config);
+    range_queries(&config, config.max_beings);
+    range_queries(&config, 2000);
+    ticks(&config);
+}
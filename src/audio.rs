@@ -0,0 +1,225 @@
+// Sounds for simulation events. The throttling is always built, actual playback only
+// with the `audio` feature, otherwise AplaySink::spawn just reports it is unsupported
+
+use std::io;
+#[cfg(feature = "audio")]
+use std::io::Write;
+#[cfg(feature = "audio")]
+use std::process::{Child, ChildStdin, Command, Stdio};
+#[cfg(feature = "audio")]
+use std::sync::mpsc::{self, Receiver, SyncSender};
+#[cfg(feature = "audio")]
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::events::SimEvent;
+
+#[cfg(feature = "audio")]
+const SAMPLE_RATE: u32 = 22050;
+#[cfg(feature = "audio")]
+const VOLUME: f32 = 0.25;  // Of full scale, the tones are meant to stay in the background
+#[cfg(feature = "audio")]
+const FADE: f32 = 0.005;  // Seconds faded in and out at both ends of a note so it doesn't click
+#[cfg(feature = "audio")]
+const QUEUE_LEN: usize = 4;  // Sounds waiting to be played, newer ones are dropped beyond this
+const MIN_GAP: Duration = Duration::from_millis(120);  // Per sound, a mass death plays one tone rather than hundreds
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    Blip,  // A being ate
+    Knell,  // A being died
+    Chime,  // A being was born
+}
+
+impl Sound {
+    pub fn for_event(event: &SimEvent) -> Option<Sound> {
+        match event {
+            SimEvent::Ate { .. } => Some(Sound::Blip),
+            SimEvent::Death { .. } => Some(Sound::Knell),
+            SimEvent::Birth { .. } => Some(Sound::Chime),
+            SimEvent::Extinction { .. } => None,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    // Frequency in Hz and length in seconds of every note, played one after another
+    #[cfg(feature = "audio")]
+    fn notes(self) -> &'static [(f32, f32)] {
+        match self {
+            Sound::Blip => &[(880.0, 0.04)],
+            Sound::Knell => &[(196.0, 0.15)],
+            Sound::Chime => &[(659.0, 0.06), (988.0, 0.08)],
+        }
+    }
+}
+
+// Anything that can play a sound, the throttling doesn't care how
+pub trait SoundSink {
+    fn play(&mut self, sound: Sound);
+}
+
+// Lets every kind of sound through at most once per gap, independently of the others
+pub struct Throttle {
+    gap: Duration,
+    last: [Option<Instant>; 3],
+}
+
+impl Throttle {
+    pub fn new(gap: Duration) -> Self {
+        Throttle { gap, last: [None; 3] }
+    }
+
+    pub fn allow(&mut self, sound: Sound, now: Instant) -> bool {
+        let last = &mut self.last[sound.index()];
+        if last.is_some_and(|at| now.duration_since(at) < self.gap) {
+            return false;
+        }
+        *last = Some(now);
+        true
+    }
+}
+
+// Plays the events of a tick through a sink, opt-in with --audio
+pub struct EventSounds<S: SoundSink> {
+    sink: S,
+    throttle: Throttle,
+}
+
+impl<S: SoundSink> EventSounds<S> {
+    pub fn new(sink: S) -> Self {
+        EventSounds { sink, throttle: Throttle::new(MIN_GAP) }
+    }
+
+    pub fn handle(&mut self, events: &[SimEvent], now: Instant) {
+        for sound in events.iter().filter_map(Sound::for_event) {
+            if self.throttle.allow(sound, now) {
+                self.sink.play(sound);
+            }
+        }
+    }
+}
+
+// Synthesized tones piped to ALSA's aplay, so no audio library is needed but only
+// Linux has sound. Writing happens on a background thread and the simulation never
+// waits on the device
+#[cfg(feature = "audio")]
+pub struct AplaySink {
+    sounds: SyncSender<Sound>,
+}
+
+#[cfg(feature = "audio")]
+impl AplaySink {
+    pub fn spawn() -> io::Result<Self> {
+        let mut child = Command::new("aplay")
+            .args(["-q", "-t", "raw", "-f", "S16_LE", "-c", "1", "-r", &SAMPLE_RATE.to_string()])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| io::Error::new(err.kind(), format!("could not start aplay: {}", err)))?;
+        let out = child.stdin.take().ok_or_else(|| io::Error::other("aplay has no stdin"))?;
+
+        let (sounds, queue) = mpsc::sync_channel(QUEUE_LEN);
+        thread::spawn(move || play(child, out, queue));
+        Ok(AplaySink { sounds })
+    }
+}
+
+#[cfg(feature = "audio")]
+impl SoundSink for AplaySink {
+    fn play(&mut self, sound: Sound) {
+        // A full queue means the device is behind, this sound is skipped
+        let _ = self.sounds.try_send(sound);
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+pub struct AplaySink;
+
+#[cfg(not(feature = "audio"))]
+impl AplaySink {
+    pub fn spawn() -> io::Result<Self> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "built without the audio feature"))
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+impl SoundSink for AplaySink {
+    fn play(&mut self, _sound: Sound) {}
+}
+
+// Runs until the simulation drops its AplaySink or aplay goes away
+#[cfg(feature = "audio")]
+fn play(mut child: Child, mut out: ChildStdin, queue: Receiver<Sound>) {
+    for sound in queue {
+        if out.write_all(&synthesize(sound)).is_err() {
+            break;
+        }
+    }
+    drop(out);
+    let _ = child.wait();
+}
+
+// Signed 16-bit little-endian mono samples of a sound's notes
+#[cfg(feature = "audio")]
+fn synthesize(sound: Sound) -> Vec<u8> {
+    let rate = SAMPLE_RATE as f32;
+    let mut bytes = Vec::new();
+    for &(frequency, length) in sound.notes() {
+        let samples = (length * rate) as usize;
+        for i in 0..samples {
+            let t = i as f32 / rate;
+            let envelope = (t / FADE).min((length - t) / FADE).clamp(0.0, 1.0);
+            let value = (t * frequency * std::f32::consts::TAU).sin() * envelope * VOLUME;
+            bytes.extend_from_slice(&((value * i16::MAX as f32) as i16).to_le_bytes());
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::DeathCause;
+
+    #[derive(Default)]
+    struct Played(Vec<Sound>);
+
+    impl SoundSink for Played {
+        fn play(&mut self, sound: Sound) {
+            self.0.push(sound);
+        }
+    }
+
+    #[test]
+    fn throttle_waits_out_the_gap_per_sound() {
+        let start = Instant::now();
+        let mut throttle = Throttle::new(Duration::from_millis(100));
+
+        assert!(throttle.allow(Sound::Knell, start));
+        assert!(!throttle.allow(Sound::Knell, start + Duration::from_millis(99)));
+        assert!(throttle.allow(Sound::Blip, start + Duration::from_millis(99)));
+        assert!(throttle.allow(Sound::Knell, start + Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn mass_death_plays_once() {
+        let mut sounds = EventSounds::new(Played::default());
+        let mut events: Vec<SimEvent> = (0..300).map(|id| SimEvent::Death { id, cause: DeathCause::Culled }).collect();
+        events.push(SimEvent::Birth { id: 301, parent_id: 1 });
+
+        sounds.handle(&events, Instant::now());
+
+        assert_eq!(sounds.sink.0, vec![Sound::Knell, Sound::Chime]);
+    }
+
+    #[test]
+    #[cfg(feature = "audio")]
+    fn notes_are_synthesized_back_to_back() {
+        let samples = (0.06 * SAMPLE_RATE as f32) as usize + (0.08 * SAMPLE_RATE as f32) as usize;
+        assert_eq!(synthesize(Sound::Chime).len(), samples * 2);
+    }
+}
@@ -1,18 +1,154 @@
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 use piston_window::*;
+use crate::corpse::Corpse;
+use crate::entity::Entity;
+use crate::environment::{self, Environment};
 use crate::genetics::Genetics;
-use crate::food::Food;
-use crate::{BASE_BEING_SIZE, ENERGY_DECAY, WINDOW_SIZE};
+use crate::pheromone::PheromoneGrid;
+use crate::food::{Food, FoodType};
+use crate::spatial::{QuadTree, SpatialGrid};
+use crate::config::{BoundaryMode, Config, PerceptionShape, ReproductionMode};
+use crate::vec2::Vec2;
+use crate::wall::Wall;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+const MATURITY_AGE: u32 = 80;  // Beings are juveniles up to this age
+const JUVENILE_FACTOR: f32 = 0.8;
+const SENESCENCE_START: f32 = 0.7;  // Fraction of max_age where decline sets in
+const SENESCENCE_MIN_FACTOR: f32 = 0.5;  // Left at max_age
+const REPRO_DRIVE_SCALE: f32 = 2.0;  // Breeding chance multiplier at full energy, half full breeds at the base rate
+const FLEE_SPEED_BOOST: f64 = 1.6;  // Fleeing prey outrun their normal pace
+const FLEE_ENERGY_COST: f32 = 0.0005;  // Extra energy per tick spent fleeing
+const FEED_FLASH_TICKS: u8 = 8;  // How long a being stays outlined after eating
+const BUD_DISTANCE: f64 = 20.0;  // Furthest an asexual child lands from its parent
+const MIN_BRIGHTNESS: f32 = 0.25;  // Starving beings fade no further than this in the energy color mode
+const TERRITORY_AGGRESSION: f64 = 1.2;  // Intruders up to this much bigger than the owner are attacked
+const PACK_RANGE: f64 = 25.0;  // Carnivores this close to a hunter's prey count as its pack
+const MAX_PACK_ALLIES: usize = 3;  // More hunters than this don't help any further
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum BeingType {
     Herbivore,
     Carnivore,
     Omnivore,
+    Scavenger,
+    Plant,  // Sessile, lives off sunlight and is grazed by herbivores
+}
+
+impl BeingType {
+    pub const ALL: [BeingType; 5] = [
+        BeingType::Herbivore,
+        BeingType::Carnivore,
+        BeingType::Omnivore,
+        BeingType::Scavenger,
+        BeingType::Plant,
+    ];
+
+    pub fn color(self) -> [f32; 4] {
+        match self {
+            BeingType::Herbivore => [0.0, 0.0, 1.0, 1.0],
+            BeingType::Carnivore => [1.0, 0.0, 0.0, 1.0],
+            BeingType::Omnivore => [1.0, 0.5, 0.0, 1.0],
+            BeingType::Scavenger => [0.6, 0.3, 0.8, 1.0],
+            BeingType::Plant => [0.0, 0.4, 0.1, 1.0],
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BeingType::Herbivore => "herbivore",
+            BeingType::Carnivore => "carnivore",
+            BeingType::Omnivore => "omnivore",
+            BeingType::Scavenger => "scavenger",
+            BeingType::Plant => "plant",
+        }
+    }
+}
+
+impl fmt::Display for BeingType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// Accepts the lowercase names Display prints, in any case
+impl FromStr for BeingType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BeingType::ALL.into_iter()
+            .find(|t| t.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                let names: Vec<&str> = BeingType::ALL.iter().map(|t| t.name()).collect();
+                format!("unknown being type {:?}, expected one of {}", s, names.join(", "))
+            })
+    }
+}
+
+// What the fill color of a being shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorMode {
+    Type,
+    Energy,  // Type color, dimmed as energy runs out
+    Age,  // Type color, graying toward max_age
+    Genes,
+}
+
+impl ColorMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            ColorMode::Type => ColorMode::Energy,
+            ColorMode::Energy => ColorMode::Age,
+            ColorMode::Age => ColorMode::Genes,
+            ColorMode::Genes => ColorMode::Type,
+        }
+    }
 }
 
-#[derive(Clone, PartialEq)]
+// Blends a color toward gray by how much of its life a being has used up,
+// full color at birth and all gray at max_age
+pub fn age_tint(color: [f32; 4], life: f32) -> [f32; 4] {
+    const GRAY: f32 = 0.5;
+    let t = life.clamp(0.0, 1.0);
+    let [red, green, blue, alpha] = color;
+    [red + (GRAY - red) * t, green + (GRAY - green) * t, blue + (GRAY - blue) * t, alpha]
+}
+
+// Brightness for an energy / max_energy ratio, from MIN_BRIGHTNESS when empty to 1 when full
+pub fn energy_brightness(ratio: f32) -> f32 {
+    MIN_BRIGHTNESS + (1.0 - MIN_BRIGHTNESS) * ratio.clamp(0.0, 1.0)
+}
+
+// Read-only view of everything around the beings for one tick
+pub struct World<'a> {
+    pub neighbors: &'a QuadTree<'a>,
+    pub foods: &'a [Food],
+    pub food_grid: &'a SpatialGrid,
+    pub corpses: &'a [Corpse],
+    pub environment: &'a Environment,
+    pub pheromones: &'a PheromoneGrid,
+    pub tick: u64,
+    pub config: &'a Config,
+}
+
+// What a single being did during its update
+pub struct UpdateOutcome {
+    pub eaten_food: Vec<usize>,  // Food reached this tick, handed out after the parallel pass
+    pub eaten_corpse: Option<usize>,
+    pub eaten_being: Option<usize>,  // Index of the caught being in the tick's snapshot
+    pub new_being: Option<Being>,
+    pub upkeep: f32,  // Energy spent on living, sunlight counts against it. Births and meals are not included
+}
+
+// Equality includes the id, so two beings are only equal if they are the same individual
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Being {
+    #[serde(default)]
+    pub id: u64,  // Handed out by the simulation, 0 until assigned
+    #[serde(default)]
+    pub parent_id: Option<u64>,
     pub x: f64,
     pub y: f64,
     pub color: [f32; 4],
@@ -21,157 +157,597 @@ pub struct Being {
     pub genetics: Genetics,
     pub age: u32,
     pub max_age: u32,
+    pub base_size: f64,
+    pub vx: f64,  // Normalized heading of the last move
+    pub vy: f64,
+    #[serde(default)]
+    pub infected: bool,
+    #[serde(default)]
+    pub infection_ticks: u32,  // How long the current infection has lasted
+    #[serde(default)]
+    pub home: (f64, f64),  // Where the being was born, the center of a carnivore's territory
+    #[serde(default)]
+    pub recent_feed: f32,  // Meals eaten lately, digested away over time, only tracked with digestion_limit
+    #[serde(skip)]
+    pub feed_flash: u8,  // Ticks left of the outline drawn after a meal
+}
+
+// Mirrors a coordinate that left 0..=max back inside and turns the heading
+// component around so the being moves away from the edge
+fn reflect(pos: f64, heading: f64, max: f64) -> (f64, f64) {
+    let max = max.max(0.0);
+    if pos < 0.0 {
+        ((-pos).min(max), heading.abs())
+    } else if pos > max {
+        ((2.0 * max - pos).max(0.0), -heading.abs())
+    } else {
+        (pos, heading)
+    }
+}
+
+impl Entity for Being {
+    fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn energy(&self) -> f32 {
+        self.energy
+    }
 }
 
 impl Being {
-   pub fn new(x: f64, y: f64, being_type: BeingType) -> Self {
-        let genetics = Genetics::new_random(being_type);
-        
-        let (color, max_age) = match being_type {
-            BeingType::Herbivore => ([0.0, 0.0, 1.0, 1.0], 3000),
-            BeingType::Carnivore => ([1.0, 0.0, 0.0, 1.0], 2000),
-            BeingType::Omnivore => ([1.0, 0.5, 0.0, 1.0], 2500),
-        };
+    pub fn new(x: f64, y: f64, being_type: BeingType, config: &Config, rng: &mut impl Rng) -> Self {
+        Being::with_genetics(x, y, being_type, Genetics::new_random(being_type, rng), config)
+    }
 
+    pub fn with_genetics(x: f64, y: f64, being_type: BeingType, genetics: Genetics, config: &Config) -> Self {
         Being {
+            id: 0,
+            parent_id: None,
             x,
             y,
-            color,
+            color: being_type.color(),
             energy: 1.0,
             being_type,
             genetics,
             age: 0,
-            max_age,
+            max_age: config.max_age(being_type),
+            base_size: config.base_being_size,
+            vx: 0.0,
+            vy: 0.0,
+            infected: false,
+            infection_ticks: 0,
+            home: (x, y),
+            recent_feed: 0.0,
+            feed_flash: 0,
         }
     }
 
     pub fn size(&self) -> f64 {
-        BASE_BEING_SIZE * self.genetics.size as f64
+        self.base_size * self.genetics.size as f64
+    }
+
+    pub fn pos(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    fn move_by(&mut self, step: Vec2) {
+        self.x += step.x;
+        self.y += step.y;
+    }
+
+    // Offset towards a point, across the world seam when wrapping
+    pub fn offset_to(&self, target: Vec2, config: &Config) -> Vec2 {
+        Vec2::new(
+            config.boundary_mode.delta(self.x, target.x, config.world_width),
+            config.boundary_mode.delta(self.y, target.y, config.world_height),
+        )
+    }
+
+    // Another carnivore standing inside this carnivore's territory
+    fn is_intruder(&self, other: &Being, config: &Config) -> bool {
+        if config.territory_radius <= 0.0 || other.being_type != BeingType::Carnivore || other.id == self.id {
+            return false;
+        }
+        let (home_x, home_y) = self.home;
+        let offset = Vec2::new(
+            config.boundary_mode.delta(home_x, other.x, config.world_width),
+            config.boundary_mode.delta(home_y, other.y, config.world_height),
+        );
+        offset.length_sq() < config.territory_radius * config.territory_radius
+    }
+
+    // Unit pull back toward home, growing from nothing at home to full strength at the
+    // territory's edge and beyond
+    pub fn home_pull(&self, config: &Config) -> Vec2 {
+        if config.territory_radius <= 0.0 {
+            return Vec2::default();
+        }
+        let offset = self.offset_to(Vec2::new(self.home.0, self.home.1), config);
+        offset.normalized() * (offset.length() / config.territory_radius).min(1.0)
+    }
+
+    // Squared distance to a being, food or corpse, across the world seam when wrapping.
+    // Compared against squared ranges to save the square root
+    pub fn distance_sq_to(&self, other: &impl Entity, config: &Config) -> f64 {
+        let (x, y) = other.position();
+        self.offset_to(Vec2::new(x, y), config).length_sq()
+    }
+
+    // The distance itself, where it is reported or scaled rather than compared
+    pub fn distance_to(&self, other: &impl Entity, config: &Config) -> f64 {
+        self.distance_sq_to(other, config).sqrt()
+    }
+
+    // Whether a target at `offset` lies in the field of view. A being that never
+    // moved has no heading yet and looks all around
+    pub fn in_view(&self, offset: Vec2, config: &Config) -> bool {
+        match config.perception_shape {
+            PerceptionShape::Circle => true,
+            PerceptionShape::Cone { half_angle } => {
+                let heading = Vec2::new(self.vx, self.vy).normalized();
+                let direction = offset.normalized();
+                if heading == Vec2::default() || direction == Vec2::default() {
+                    return true;
+                }
+                heading.x * direction.x + heading.y * direction.y >= half_angle.cos()
+            },
+        }
+    }
+
+    // Scales speed and perception with age, juveniles are a bit slower and
+    // old beings decline linearly over the last part of their life
+    pub fn age_factor(&self) -> f32 {
+        if self.age <= MATURITY_AGE {
+            return JUVENILE_FACTOR;
+        }
+
+        let life = self.age as f32 / self.max_age.max(1) as f32;
+        if life <= SENESCENCE_START {
+            return 1.0;
+        }
+
+        let decline = ((life - SENESCENCE_START) / (1.0 - SENESCENCE_START)).min(1.0);
+        1.0 - decline * (1.0 - SENESCENCE_MIN_FACTOR)
+    }
+
+    // Perception after aging and the day/night cycle
+    pub fn effective_perception(&self, tick: u64, config: &Config) -> f64 {
+        let brightness = environment::time_of_day(tick, config.day_length);
+        let factor = self.age_factor() * environment::perception_factor(brightness, self.being_type);
+        (self.genetics.perception * factor) as f64
+    }
+
+    // Speed after aging effects
+    pub fn speed(&self) -> f64 {
+        (self.genetics.speed * self.age_factor()) as f64
+    }
+
+    // Same-type beings within one body size, the being's own entry in the tree excluded
+    fn crowding(&self, neighbors: &QuadTree) -> usize {
+        neighbors.query_range((self.x, self.y), self.size())
+            .into_iter()
+            .filter(|(_, b)| b.being_type == self.being_type)
+            .count()
+            .saturating_sub(1)
+    }
+
+    // Infected beings catch nothing new, they lose extra energy until the disease either
+    // clears or kills them. Healthy ones may catch it from anyone they touch. Returns the
+    // energy the disease took
+    fn update_infection(&mut self, neighbors: &QuadTree, config: &Config, rng: &mut impl Rng) -> f32 {
+        if self.infected {
+            let before = self.energy;
+            self.infection_ticks += 1;
+            self.energy -= config.disease_decay;
+            if self.infection_ticks >= config.disease_duration {
+                self.infected = false;
+                self.infection_ticks = 0;
+                if rng.random_bool(config.disease_lethality.clamp(0.0, 1.0) as f64) {
+                    self.energy = 0.0;
+                }
+            }
+            return before - self.energy;
+        }
+
+        if config.disease_transmission <= 0.0 {
+            return 0.0;
+        }
+
+        let contacts = neighbors.query_range((self.x, self.y), self.size())
+            .into_iter()
+            .filter(|(_, b)| b.infected && b.id != self.id)
+            .count();
+        if contacts > 0 {
+            // Each contact is an independent chance to pass it on
+            let escape = (1.0 - config.disease_transmission.clamp(0.0, 1.0)).powi(contacts as i32);
+            if rng.random_bool(1.0 - escape as f64) {
+                self.infected = true;
+            }
+        }
+        0.0
+    }
+
+    pub fn is_hungry(&self, config: &Config) -> bool {
+        self.energy < config.hunger_threshold * self.genetics.max_energy
+    }
+
+    pub fn is_starving(&self, config: &Config) -> bool {
+        self.energy < config.starvation_threshold * self.genetics.max_energy
+    }
+
+    // Eating can only top energy up to the genetic ceiling, metabolism scales the yield.
+    // Returns what was actually gained
+    pub fn gain_energy(&mut self, amount: f32) -> f32 {
+        let before = self.energy;
+        self.energy = (self.energy + amount * self.genetics.metabolism).min(self.genetics.max_energy);
+        self.energy - before
+    }
+
+    // Food is only claimed during the update, several beings may reach the same piece
+    // and the simulation decides who gets it. Omnivores get the most out of it
+    pub fn food_value(&self, food: &Food, config: &Config) -> f32 {
+        let share = if self.being_type == BeingType::Omnivore { config.omnivore_forage_bonus } else { 1.0 };
+        food.energy * share
+    }
+
+    // Prey is claimed the same way. Carnivores and omnivores lose part of a kill, a
+    // herbivore grazing a plant gets all of it
+    pub fn prey_value(&self, prey: &Being, config: &Config) -> f32 {
+        let efficiency = match self.being_type {
+            BeingType::Carnivore => config.carnivore_predation_efficiency,
+            BeingType::Omnivore => config.omnivore_predation_efficiency,
+            _ => 1.0,
+        };
+        prey.energy * efficiency
+    }
+
+    // What a meal worth `amount` would add, with a digestion limit every recent meal
+    // makes the next one worth less
+    pub fn meal_gain(&self, amount: f32, config: &Config) -> f32 {
+        let digested = if config.digestion_limit { amount / (1.0 + self.recent_feed) } else { amount };
+        (self.energy + digested * self.genetics.metabolism).min(self.genetics.max_energy) - self.energy
     }
 
-    pub fn update(&mut self, beings: &[Being], foods: &[Food]) -> (Vec<usize>, Option<Being>) {
-	// Filter beings based on type before processing
-	let filtered_beings: Vec<&Being> = match self.being_type {
-            BeingType::Herbivore => Vec::new(),  // Herbivores don't need other beings
-            BeingType::Carnivore => beings.iter()
-		.filter(|b| matches!(b.being_type, BeingType::Herbivore | BeingType::Omnivore))
+    // A meal, as opposed to sunlight, also makes the being flash briefly
+    pub fn eat(&mut self, amount: f32, config: &Config) {
+        if config.digestion_limit {
+            self.gain_energy(amount / (1.0 + self.recent_feed));
+            self.recent_feed += 1.0;
+        } else {
+            self.gain_energy(amount);
+        }
+        self.feed_flash = FEED_FLASH_TICKS;
+    }
+
+    pub fn update(&mut self, world: &World, rng: &mut impl Rng) -> UpdateOutcome {
+        let World { neighbors, foods, food_grid, corpses, environment, pheromones, tick, config } = *world;
+        let perception_range = self.effective_perception(tick, config);  // Movement based on perception
+
+	// Starving carnivores turn on their own kind, decided before this tick's upkeep
+	let cannibal = config.allow_cannibalism && self.is_starving(config);
+
+	// Only look at beings inside the perception circle, filtered by type
+	let filtered_beings: Vec<(usize, &Being)> = match self.being_type {
+            BeingType::Scavenger | BeingType::Plant => Vec::new(),  // Only interested in corpses or nothing at all
+            BeingType::Herbivore => neighbors.query_range((self.x, self.y), perception_range)
+		.into_iter()
+		.filter(|(_, b)| b.being_type != BeingType::Omnivore && b.being_type != BeingType::Scavenger)
+		.collect(),
+            BeingType::Carnivore => neighbors.query_range((self.x, self.y), perception_range * 1.5)
+		.into_iter()
+		.filter(|(_, b)| match b.being_type {
+		    BeingType::Plant => false,
+		    // Fellow hunters matter as prey or, with pack hunting, as allies
+		    BeingType::Carnivore => cannibal || config.pack_bonus > 0.0 || self.is_intruder(b, config),
+		    _ => true,
+		})
 		.collect(),
-            BeingType::Omnivore => beings.iter()
-		.filter(|b| b.being_type != self.being_type)
+            BeingType::Omnivore => neighbors.query_range((self.x, self.y), perception_range)
+		.into_iter()
+		.filter(|(_, b)| b.being_type != self.being_type)
 		.collect(),
 	};
         
         // Original update logic using filtered_beings instead of beings
-        let mut rng = rand::rng();
         self.age += 1;
-        self.energy -= ENERGY_DECAY * (self.genetics.size + self.genetics.speed);  // Lose energy based on size and speed
+        self.feed_flash = self.feed_flash.saturating_sub(1);
+        if config.digestion_limit {
+            self.recent_feed *= 1.0 - config.digestion_rate;
+        }
+        // Lose energy based on size and speed, a bigger energy store and a faster metabolism cost more upkeep,
+        // and so does a cold climate
+        let decay = config.energy_decay * config.metabolic_model.load(self.genetics.size, self.genetics.speed)
+            * self.genetics.max_energy * self.genetics.metabolism
+            * environment.decay_factor(self.x, self.y, config);
+        self.energy -= decay;
+        // Competition with same-type beings packed on top of each other
+        let crowding = config.crowding_penalty * self.crowding(neighbors) as f32;
+        self.energy -= crowding;
+        let mut upkeep = decay + crowding;
+        if self.near_edge(config) {
+            self.energy -= config.edge_drain;
+            upkeep += config.edge_drain;
+        }
+        upkeep += self.update_infection(neighbors, config, rng);
+
+        // Herbivores and omnivores run from predators before anything else
+        let fled = matches!(self.being_type, BeingType::Herbivore | BeingType::Omnivore) && self.try_flee(&filtered_beings, config);
+        if fled {
+            upkeep += FLEE_ENERGY_COST;
+        }
         
-        let perception_range = self.genetics.perception as f64;  // Movement based on perception
         let mut eaten_food_indices = Vec::new();
+        let mut eaten_corpse = None;
         let mut new_being = None;
+        let start = self.pos();
         
-        match self.being_type {
+        let prey = match self.being_type {
             BeingType::Herbivore => {
-                self.update_herbivore(foods, perception_range, &mut rng, &mut eaten_food_indices)
-            },
-            BeingType::Carnivore => {
-                if let Some(prey) = self.update_carnivore(&filtered_beings, perception_range, &mut rng) {
-                    return (vec![], Some(prey));
+                let eaten = self.update_herbivore(&filtered_beings, foods, food_grid, perception_range, fled, config, rng, &mut eaten_food_indices);
+                // Drawn a little toward well-trodden paths
+                if config.pheromone_deposit > 0.0 {
+                    self.move_by(pheromones.gradient_at(self.x, self.y).normalized() * self.speed() * config.pheromone_weight);
                 }
+                eaten
             },
+            BeingType::Carnivore => self.update_carnivore(&filtered_beings, perception_range, cannibal, config, rng),
             BeingType::Omnivore => {
-                if let Some((prey, food_indices)) = self.update_omnivore(&filtered_beings, foods, perception_range, &mut rng) {
-                    if let Some(p) = prey {
-                        return (food_indices, Some(p));
-                    }
+                if fled {
+                    None
+                } else {
+                    let (prey, food_indices) = self.update_omnivore(&filtered_beings, foods, food_grid, perception_range, config, rng);
                     eaten_food_indices = food_indices;
+                    prey
                 }
             },
+            BeingType::Scavenger => {
+                eaten_corpse = self.update_scavenger(corpses, perception_range, config, rng);
+                None
+            },
+            BeingType::Plant => {
+                upkeep -= self.update_plant(tick, config);
+                None
+            },
+        };
+
+        self.update_heading(start);
+        self.settle(start, config);
+        debug_assert!(self.x.is_finite() && self.y.is_finite(), "being {} moved to a non-finite position", self.id);
+
+        if prey.is_some() {
+            return UpdateOutcome { eaten_food: eaten_food_indices, eaten_corpse, eaten_being: prey, new_being: None, upkeep };
         }
         
-        self.x = self.x.max(0.0).min(WINDOW_SIZE - self.size());
-        self.y = self.y.max(0.0).min(WINDOW_SIZE - self.size());
-        
-        if self.can_replicate() {
-            new_being = Some(self.replicate());
+        // Sexual pairing needs two mutable parents, so it happens after the parallel pass
+        if self.reproduces_asexually(config) && self.can_replicate(config, rng) {
+            new_being = Some(self.replicate(config, rng));
+            self.trace(format_args!("reproduced"));
         }
         
-        (eaten_food_indices, new_being)
+        UpdateOutcome { eaten_food: eaten_food_indices, eaten_corpse, eaten_being: None, new_being, upkeep }
     }
 
+    // Grazes the closest of food and plant-beings, returns the index of an eaten plant.
+    // `neighbors` holds the plants, herbivores and carnivores within perception
+    #[allow(clippy::too_many_arguments)]
     pub fn update_herbivore(
 	&mut self,
+	neighbors: &[(usize, &Being)],
+	foods: &[Food],
+	food_grid: &SpatialGrid,
+	perception_range: f64,
+	fled: bool,
+	config: &Config,
+	rng: &mut impl Rng,
+	eaten_food_indices: &mut Vec<usize>,
+    ) -> Option<usize> {
+	// Flocking is blended on top of whatever the being does on its own
+	let flock = if config.flocking_weight > 0.0 {
+            self.flock_force(neighbors, config) * config.flocking_weight
+	} else {
+            Vec2::default()
+	};
+
+	let eaten_plant = if fled {
+            None
+	} else {
+            self.forage(neighbors, foods, food_grid, perception_range, config, rng, eaten_food_indices)
+	};
+	self.move_by(flock * self.speed());
+	eaten_plant
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn forage(
+	&mut self,
+	neighbors: &[(usize, &Being)],
 	foods: &[Food],
+	food_grid: &SpatialGrid,
 	perception_range: f64,
+	config: &Config,
 	rng: &mut impl Rng,
 	eaten_food_indices: &mut Vec<usize>,
-    ) {
-	// Only look at food, ignore other beings completely
-	if let Some((idx, nearest_food)) = foods.iter().enumerate().min_by_key(|(_, f)| {
-            let dx = f.x - self.x;
-            let dy = f.y - self.y;
-            ((dx * dx + dy * dy) * 1000.0) as i32
-	}) {
-            let dx = nearest_food.x - self.x;
-            let dy = nearest_food.y - self.y;
-            let distance = (dx * dx + dy * dy).sqrt();
-            
-            if distance < perception_range {
-		self.x += dx / distance * self.genetics.speed as f64 * 1.5;
-		self.y += dy / distance * self.genetics.speed as f64 * 1.5;
+    ) -> Option<usize> {
+	// A full being just wanders
+	if !self.is_hungry(config) {
+            self.random_movement(rng);
+            self.trace(format_args!("wandered, not hungry"));
+            return None;
+	}
+
+	// Never touch carrion
+	let edible = |idx: usize| {
+            foods[idx].food_type != FoodType::Carrion && self.in_view(self.offset_to(foods[idx].pos(), config), config)
+	};
+	let food = food_grid.nearest_within(self.x, self.y, perception_range, edible)
+            .map(|idx| (idx, self.offset_to(foods[idx].pos(), config)));
+	let plant = neighbors.iter()
+            .filter(|(_, b)| b.being_type == BeingType::Plant)
+            .map(|&(idx, b)| (idx, b, self.offset_to(b.pos(), config)))
+            .filter(|(_, _, offset)| self.in_view(*offset, config))
+            .min_by(|a, b| a.2.length_sq().total_cmp(&b.2.length_sq()));
+
+	match (food, plant) {
+            (Some((_, food_offset)), Some((idx, plant, offset))) if offset.length_sq() < food_offset.length_sq() => {
+		self.graze(idx, plant, offset)
+            },
+            (None, Some((idx, plant, offset))) => self.graze(idx, plant, offset),
+            (Some((idx, offset)), _) => {
+		let distance = offset.length();
+		self.move_by(offset.normalized() * self.speed() * 1.5);
 		
 		if distance < self.size() / 2.0 + 2.5 {
                     eaten_food_indices.push(idx);
-                    self.energy += nearest_food.energy;
+                    self.trace(format_args!("reached food {}", idx));
+		} else {
+                    self.trace(format_args!("sought food {} at distance {:.1}", idx, distance));
 		}
-            } else {
+		None
+            },
+            (None, None) => {
 		self.random_movement(rng);
+		self.trace(format_args!("wandered, no food in sight"));
+		None
+            },
+	}
+    }
+
+    // Boids steering from same-type neighbors: keep some distance, match their heading
+    // and drift toward their centroid. Unit length, or zero without flockmates
+    pub fn flock_force(&self, neighbors: &[(usize, &Being)], config: &Config) -> Vec2 {
+	let mut separation = Vec2::default();
+	let mut alignment = Vec2::default();
+	let mut centroid = Vec2::default();
+	let mut count = 0;
+
+	for (_, other) in neighbors.iter().filter(|(_, b)| b.being_type == self.being_type && b.id != self.id) {
+            let offset = self.offset_to(other.pos(), config);
+            if offset.length_sq() < self.size() * self.size() {
+		separation += offset.normalized() * -1.0;
             }
-	} else {
-            self.random_movement(rng);
+            alignment += Vec2::new(other.vx, other.vy);
+            centroid += offset;
+            count += 1;
+	}
+
+	if count == 0 {
+            return Vec2::default();
 	}
+
+	let cohesion = (centroid * (1.0 / count as f64)).normalized();
+	(separation.normalized() * 1.5 + alignment.normalized() + cohesion).normalized()
+    }
+
+    // Direction away from the nearest carnivore that could catch this being, if any
+    pub fn flee_from(&self, predators: &[(usize, &Being)], config: &Config) -> Option<Vec2> {
+	predators.iter()
+            .filter(|(_, b)| b.being_type == BeingType::Carnivore && self.size() < b.size() * config.carnivore_prey_size_ratio)
+            .map(|(_, b)| self.offset_to(b.pos(), config))
+            .min_by(|a, b| a.length_sq().total_cmp(&b.length_sq()))
+            .map(|offset| {
+		let away = offset.normalized() * -1.0;
+		// Sharing a spot with the predator gives no direction, pick any
+		if away == Vec2::default() { Vec2::new(1.0, 0.0) } else { away }
+            })
+    }
+
+    // Running from a predator overrides everything else and costs extra energy
+    fn try_flee(&mut self, beings: &[(usize, &Being)], config: &Config) -> bool {
+	match self.flee_from(beings, config) {
+            Some(away) => {
+		self.move_by(away * self.speed() * FLEE_SPEED_BOOST);
+		self.energy -= FLEE_ENERGY_COST;
+		self.trace(format_args!("fled"));
+		true
+            },
+            None => false,
+	}
+    }
+
+    fn graze(&mut self, idx: usize, plant: &Being, offset: Vec2) -> Option<usize> {
+	let distance = offset.length();
+	self.move_by(offset.normalized() * self.speed() * 1.5);
+
+	if distance < self.size() / 2.0 + plant.size() / 2.0 {
+            self.trace(format_args!("reached plant {}", plant.id));
+            return Some(idx);
+	}
+	self.trace(format_args!("sought plant {} at distance {:.1}", plant.id, distance));
+	None
+    }
+
+    // Photosynthesis follows the daylight, plants never move. Returns the energy gained
+    pub fn update_plant(&mut self, tick: u64, config: &Config) -> f32 {
+	let brightness = environment::time_of_day(tick, config.day_length);
+	self.gain_energy(config.plant_photosynthesis * brightness)
     }
     
-    pub fn update_carnivore(&mut self, beings: &[&Being], perception_range: f64, rng: &mut impl Rng) -> Option<Being> {
-	// Find ALL potential prey in perception range (not just nearest)
+    pub fn update_carnivore(
+        &mut self,
+        beings: &[(usize, &Being)],
+        perception_range: f64,
+        cannibal: bool,
+        config: &Config,
+        rng: &mut impl Rng,
+    ) -> Option<usize> {
+	let patrol = self.home_pull(config) * self.speed();
+	if !self.is_hungry(config) {
+            self.random_movement(rng);
+            self.move_by(patrol);
+            self.trace(format_args!("wandered, not hungry"));
+            return None;
+	}
+
+	// Find ALL potential prey in perception range (not just nearest), fellow
+	// carnivores only show up here when starving and must be weaker in every way,
+	// or when they intrude on this one's territory
+	let detection_sq = (perception_range * 1.5).powi(2);  // Larger detection range
 	let mut potential_prey: Vec<_> = beings.iter()
-            .filter(|&&b| b.size() < self.size() * 1.1)  // Slightly larger threshold
-            .filter(|&&b| {
-		let dx = b.x - self.x;
-		let dy = b.y - self.y;
-		(dx * dx + dy * dy).sqrt() < perception_range * 1.5  // Larger detection range
+            .filter(|(_, b)| match b.being_type {
+		BeingType::Carnivore if self.is_intruder(b, config) => b.size() < self.size() * TERRITORY_AGGRESSION,
+		BeingType::Carnivore => cannibal && b.size() < self.size() && b.energy < self.energy,
+		_ => b.size() < self.size() * config.carnivore_prey_size_ratio,
+            })
+            .filter(|(_, b)| {
+		let offset = self.offset_to(b.pos(), config);
+		offset.length_sq() < detection_sq && self.in_view(offset, config)
             })
             .collect();
 	
 	// If we found prey
 	if !potential_prey.is_empty() {
             // Sort by distance AND energy (prioritize closer, higher energy prey)
-            potential_prey.sort_by(|&&a, &&b| {
-		let dist_a = (a.x - self.x).powi(2) + (a.y - self.y).powi(2);
-		let dist_b = (b.x - self.x).powi(2) + (b.y - self.y).powi(2);
-		let weight_a = dist_a * (1.1 - a.energy as f64);
-		let weight_b = dist_b * (1.1 - b.energy as f64);
-		weight_a.partial_cmp(&weight_b).unwrap()
-            });
+            let weight = |b: &Being| self.distance_sq_to(b, config) * (1.1 - b.energy as f64);
+            potential_prey.sort_by(|(_, a), (_, b)| weight(a).total_cmp(&weight(b)));
 	    
-            let target = potential_prey[0];
-            let dx = target.x - self.x;
-            let dy = target.y - self.y;
-            let distance = (dx * dx + dy * dy).sqrt();
+            let (target_idx, target) = *potential_prey[0];
+            let offset = self.offset_to(target.pos(), config);
+            let distance = offset.length();
 	    
             // More aggressive chasing
             let speed_multiplier = if distance < perception_range { 3.5 } else { 2.5 };
-            self.x += dx / distance * self.genetics.speed as f64 * speed_multiplier;
-            self.y += dy / distance * self.genetics.speed as f64 * speed_multiplier;
+            self.move_by(offset.normalized() * self.speed() * speed_multiplier);
 	    
-            if distance < self.size() / 2.0 + target.size() / 2.0 {
-		self.energy += target.energy * 0.95;
-		return Some((*target).clone());
+            // Every other carnivore closing in on the same prey makes it easier to catch
+            let allies = beings.iter()
+		.filter(|(_, b)| b.being_type == BeingType::Carnivore && b.id != self.id && b.id != target.id)
+		.filter(|(_, b)| b.distance_sq_to(target, config) < PACK_RANGE * PACK_RANGE)
+		.count()
+		.min(MAX_PACK_ALLIES);
+            let reach = (self.size() / 2.0 + target.size() / 2.0) * (1.0 + config.pack_bonus * allies as f64);
+
+            if distance < reach {
+		self.trace(format_args!("caught prey {} with {} allies", target.id, allies));
+		return Some(target_idx);
             }
+            self.trace(format_args!("chased prey {} at distance {:.1}", target.id, distance));
 	} else {
             // More purposeful wandering when no prey is visible
-            self.x += rng.random_range(-1.0..1.0) * self.genetics.speed as f64 * 1.5;
-            self.y += rng.random_range(-1.0..1.0) * self.genetics.speed as f64 * 1.5;
+            self.x += rng.random_range(-1.0..1.0) * self.speed() * 1.5;
+            self.y += rng.random_range(-1.0..1.0) * self.speed() * 1.5;
+            self.move_by(patrol);
+            self.trace(format_args!("wandered, no prey in sight"));
 	}
 	
 	None
@@ -179,105 +755,868 @@ impl Being {
     
     pub fn update_omnivore(
 	&mut self,
-	beings: &[&Being],
+	beings: &[(usize, &Being)],
 	foods: &[Food],
+	food_grid: &SpatialGrid,
 	perception_range: f64,
+	config: &Config,
 	rng: &mut impl Rng,
-    ) -> Option<(Option<Being>, Vec<usize>)> {
+    ) -> (Option<usize>, Vec<usize>) {
 	let mut eaten_food_indices = Vec::new();
 
-	// Alternate between food and smaller beings
-	if rng.random_bool(0.7) {
+	if !self.is_hungry(config) {
+            self.random_movement(rng);
+            self.trace(format_args!("wandered, not hungry"));
+            return (None, eaten_food_indices);
+	}
+
+	// Hunt smaller beings or forage as the diet gene says, only wander when nothing was pursued
+	let mut pursued = false;
+	if rng.random_bool((self.genetics.diet_preference as f64).clamp(0.0, 1.0)) {
             if let Some(target) = beings.iter()
-		.filter(|&&b| b.size() < self.size() * 0.9)
-		.min_by_key(|&&b| {
-                    let dx = b.x - self.x;
-                    let dy = b.y - self.y;
-                    ((dx * dx + dy * dy) * (1.0 + b.energy as f64)) as i32
+		.filter(|(_, b)| b.size() < self.size() * config.omnivore_prey_size_ratio)
+		.map(|&(idx, b)| (idx, b, self.offset_to(b.pos(), config)))
+		.filter(|(_, _, offset)| self.in_view(*offset, config))
+		.min_by(|(_, a, a_off), (_, b, b_off)| {
+                    let weight_a = a_off.length_sq() * (1.0 + a.energy as f64);
+                    let weight_b = b_off.length_sq() * (1.0 + b.energy as f64);
+                    weight_a.total_cmp(&weight_b)
 		})
             {
-		let dx = target.x - self.x;
-		let dy = target.y - self.y;
-		let distance = (dx * dx + dy * dy).sqrt();
+		let (target_idx, target, offset) = target;
+		let distance = offset.length();
 		
 		if distance < perception_range {
-                    self.x += dx / distance * self.genetics.speed as f64 * 2.2;
-                    self.y += dy / distance * self.genetics.speed as f64 * 2.2;
+                    self.move_by(offset.normalized() * self.speed() * 2.2);
+                    pursued = true;
                     
                     if distance < self.size() / 2.0 + target.size() / 2.0 {
-			self.energy += target.energy * 0.85;
-			return Some((Some((*target).clone()), vec![]));  
+			self.trace(format_args!("caught prey {}", target.id));
+			return (Some(target_idx), vec![]);
                     }
+                    self.trace(format_args!("chased prey {} at distance {:.1}", target.id, distance));
 		}
             }
 	} else {
-            if let Some((idx, nearest_food)) = foods.iter().enumerate()
-		.min_by_key(|(_, f)| {
-                    let dx = f.x - self.x;
-                    let dy = f.y - self.y;
-                    ((dx * dx + dy * dy) * 1000.0) as i32
-		}) 
-            {
-		let dx = nearest_food.x - self.x;
-		let dy = nearest_food.y - self.y;
-		let distance = (dx * dx + dy * dy).sqrt();
+            let visible = |idx: usize| self.in_view(self.offset_to(foods[idx].pos(), config), config);
+            if let Some(idx) = food_grid.nearest_within(self.x, self.y, perception_range * 1.2, visible) {
+		let nearest_food = &foods[idx];
+		let offset = self.offset_to(nearest_food.pos(), config);
+		let distance = offset.length();
 		
-		if distance < perception_range * 1.2 {
-                    self.x += dx / distance * self.genetics.speed as f64 * 1.8;
-                    self.y += dy / distance * self.genetics.speed as f64 * 1.8;
-                    
-                    if distance < self.size() / 2.0 + 2.5 {
-			eaten_food_indices.push(idx);
-			self.energy += nearest_food.energy * 1.2;
-                    }
+		self.move_by(offset.normalized() * self.speed() * 1.8);
+		pursued = true;
+		
+		if distance < self.size() / 2.0 + 2.5 {
+                    eaten_food_indices.push(idx);
+                    self.trace(format_args!("reached food {}", idx));
+		} else {
+                    self.trace(format_args!("sought food {} at distance {:.1}", idx, distance));
 		}
             }
 	}
 	
-	self.random_movement(rng);
-	Some((None, eaten_food_indices))
+	if !pursued {
+            self.random_movement(rng);
+            self.trace(format_args!("wandered, nothing to pursue"));
+	}
+	(None, eaten_food_indices)
     }
     
+    // Heads for the nearest corpse in range, returns its index once reached
+    pub fn update_scavenger(
+	&mut self,
+	corpses: &[Corpse],
+	perception_range: f64,
+	config: &Config,
+	rng: &mut impl Rng,
+    ) -> Option<usize> {
+	if !self.is_hungry(config) {
+            self.random_movement(rng);
+            self.trace(format_args!("wandered, not hungry"));
+            return None;
+	}
+
+	let nearest = corpses.iter().enumerate()
+            .map(|(idx, c)| (idx, self.offset_to(c.pos(), config)))
+            .filter(|(_, offset)| offset.length_sq() < perception_range * perception_range && self.in_view(*offset, config))
+            .min_by(|a, b| a.1.length_sq().total_cmp(&b.1.length_sq()));
+
+	if let Some((idx, offset)) = nearest {
+            let distance = offset.length();
+            self.move_by(offset.normalized() * self.speed() * 1.5);
+
+            if distance < self.size() / 2.0 + 2.0 {
+		self.trace(format_args!("reached corpse {}", idx));
+		return Some(idx);
+            }
+            self.trace(format_args!("sought corpse {} at distance {:.1}", idx, distance));
+	} else {
+            self.random_movement(rng);
+            self.trace(format_args!("wandered, no corpse in sight"));
+	}
+
+	None
+    }
+
+    // Within edge_margin of a world edge, a wrapping world has none
+    pub fn near_edge(&self, config: &Config) -> bool {
+        if config.edge_margin <= 0.0 || config.boundary_mode == BoundaryMode::Wrap {
+            return false;
+        }
+        let size = self.size();
+        let distance = self.x.min(self.y)
+            .min(config.world_width - size - self.x)
+            .min(config.world_height - size - self.y);
+        distance < config.edge_margin
+    }
+
+    // Moves without changing the heading, as when shoved by a neighbor
+    pub fn push_by(&mut self, delta: Vec2, config: &Config) {
+        let start = self.pos();
+        self.move_by(delta);
+        self.settle(start, config);
+    }
+
+    // Brings the being back inside the world edges and out of any wall it moved into
+    fn settle(&mut self, start: Vec2, config: &Config) {
+        match config.boundary_mode {
+            BoundaryMode::Clamp => {
+                self.x = self.x.max(0.0).min(config.world_width - self.size());
+                self.y = self.y.max(0.0).min(config.world_height - self.size());
+            },
+            BoundaryMode::Wrap => {
+                self.x = self.x.rem_euclid(config.world_width);
+                self.y = self.y.rem_euclid(config.world_height);
+            },
+            BoundaryMode::Reflect => {
+                let size = self.size();
+                (self.x, self.vx) = reflect(self.x, self.vx, config.world_width - size);
+                (self.y, self.vy) = reflect(self.y, self.vy, config.world_height - size);
+            },
+        }
+        self.resolve_walls(start, &config.walls);
+    }
+
+    // Undoes a move into a wall, sliding along it when only one axis is blocked
+    fn resolve_walls(&mut self, start: Vec2, walls: &[Wall]) {
+        let size = self.size();
+        let blocked = |x: f64, y: f64| walls.iter().any(|w| w.overlaps_square(x, y, size));
+        // A being that starts inside a wall (e.g. born there) is let out freely
+        if !blocked(self.x, self.y) || blocked(start.x, start.y) {
+            return;
+        }
+
+        if !blocked(self.x, start.y) {
+            self.y = start.y;
+        } else if !blocked(start.x, self.y) {
+            self.x = start.x;
+        } else {
+            (self.x, self.y) = (start.x, start.y);
+        }
+    }
+
+    // One decision for --verbose, the message is only formatted when tracing is on
+    fn trace(&self, what: fmt::Arguments) {
+        log::trace!("{} {} at ({:.1}, {:.1}): {}", self.being_type, self.id, self.x, self.y, what);
+    }
+
+    // Points the heading along the move from `start`, keeps the previous heading when
+    // the being didn't move
+    fn update_heading(&mut self, start: Vec2) {
+        let heading = start.normalized_toward(self.pos());
+        if heading != Vec2::default() {
+            self.vx = heading.x;
+            self.vy = heading.y;
+        }
+    }
+
     pub fn random_movement(&mut self, rng: &mut impl Rng) {
-        self.x += rng.random_range(-1.0..1.0) * self.genetics.speed as f64;
-        self.y += rng.random_range(-1.0..1.0) * self.genetics.speed as f64;
+        self.x += rng.random_range(-1.0..1.0) * self.speed();
+        self.y += rng.random_range(-1.0..1.0) * self.speed();
     }
 
-    pub fn can_replicate(&self) -> bool {
-        let mut rng = rand::rng();
-        let base_chance = match self.being_type {
-            BeingType::Carnivore => 0.0016,
-            BeingType::Omnivore => 0.0013,
-            BeingType::Herbivore => 0.0011,
-        };
+    // Plants always bud on their own, whatever the reproduction mode
+    pub fn reproduces_asexually(&self, config: &Config) -> bool {
+        config.reproduction_mode == ReproductionMode::Asexual || self.being_type == BeingType::Plant
+    }
+
+    // Energy an asexual child starts with
+    pub fn budded_energy(&self) -> f32 {
+        self.energy * 0.5
+    }
+
+    // Energy a child of two parents starts with
+    pub fn mated_energy(a: &Being, b: &Being) -> f32 {
+        (a.energy + b.energy) * 0.25
+    }
+
+    // What a parent keeps after paying its share of a child plus the flat repro_cost
+    pub fn energy_after_birth(&self, config: &Config) -> f32 {
+        let kept = if self.reproduces_asexually(config) { 0.5 } else { 0.75 };
+        self.energy * kept - config.repro_cost
+    }
+
+    // Energy as a fraction of what this being can store
+    pub fn energy_ratio(&self) -> f32 {
+        (self.energy / self.genetics.max_energy).clamp(0.0, 1.0)
+    }
+
+    pub fn can_replicate(&self, config: &Config, rng: &mut impl Rng) -> bool {
+        let base_chance = config.repro_chance(self.being_type);
+        // Well-fed beings breed more often, the chance grows smoothly with energy
+        let drive = REPRO_DRIVE_SCALE * self.energy_ratio();
         
-        self.energy > 0.8 &&
-            rng.random_range(0.0..1.0) < (base_chance * self.genetics.reproduction_rate) &&
-            self.age > 80 &&
+        self.energy > config.min_repro_energy(self.being_type) &&
+            self.energy_after_birth(config) >= config.repro_survival_floor &&
+            // A mated child's energy depends on the partner, mate_pairs checks that one
+            (!self.reproduces_asexually(config) || self.budded_energy() >= config.min_viable_energy) &&
+            rng.random_range(0.0..1.0) < (base_chance * self.genetics.reproduction_rate * drive) &&
+            self.age > config.min_repro_age(self.being_type) &&
             self.age < self.max_age
     }
 
-    pub  fn replicate(&mut self) -> Being {
+    pub fn replicate(&mut self, config: &Config, rng: &mut impl Rng) -> Being {
         let mut child = self.clone(); // Ensure this copies all fields properly
-        let mut rng = rand::rng();
         
-        child.x += rng.random_range(-20.0..20.0);
-        child.y += rng.random_range(-20.0..20.0);
-        child.energy = self.energy * 0.5;
-        child.genetics = self.genetics.mutate();
+        child.id = 0;
+        child.parent_id = Some(self.id);
+        child.infected = false;
+        child.infection_ticks = 0;
+        child.recent_feed = 0.0;
+        child.feed_flash = 0;
+        // Somewhere on a ring around the parent, at least a body size away so the two don't overlap
+        let min_distance = self.size();
+        let angle = rng.random_range(0.0..std::f64::consts::TAU);
+        let distance = rng.random_range(min_distance..=BUD_DISTANCE.max(min_distance));
+        let mut offset = Vec2::new(angle.cos(), angle.sin()) * distance;
+        child.energy = self.budded_energy();
+        child.genetics = self.genetics.mutate(config.mutation_rate, config.mutation_model, rng);
         child.age = 0;
-        self.energy *= 0.5;
+        // Aim back inward where the ring crosses a world edge, clamping would otherwise
+        // put the child back on top of a parent by the edge
+        if config.boundary_mode != BoundaryMode::Wrap {
+            let size = child.size();
+            if !(0.0..=config.world_width - size).contains(&(self.x + offset.x)) {
+                offset.x = -offset.x;
+            }
+            if !(0.0..=config.world_height - size).contains(&(self.y + offset.y)) {
+                offset.y = -offset.y;
+            }
+        }
+        // Kept inside the world and out of walls like any other move, once its own size is known
+        child.push_by(offset, config);
+        child.home = (child.x, child.y);
+        self.energy = self.energy_after_birth(config);
         
         child
     }
 
-    pub fn draw(&self, transform: math::Matrix2d, g: &mut G2d) {
+    // Both parents pay a quarter of their energy into the child, plus repro_cost
+    pub fn mate(&mut self, partner: &mut Being, config: &Config, rng: &mut impl Rng) -> Being {
+        let mut child = self.clone();
+
+        child.id = 0;
+        child.parent_id = Some(self.id);
+        child.infected = false;
+        child.infection_ticks = 0;
+        child.recent_feed = 0.0;
+        child.feed_flash = 0;
+        // Born between the parents, across the world seam when wrapping
+        let midpoint = self.offset_to(partner.pos(), config) * 0.5;
+        let offset = midpoint + Vec2::new(rng.random_range(-10.0..10.0), rng.random_range(-10.0..10.0));
+        child.energy = Being::mated_energy(self, partner);
+        child.genetics = self.genetics.crossover(&partner.genetics, rng).mutate(config.mutation_rate, config.mutation_model, rng);
+        child.age = 0;
+        child.push_by(offset, config);
+        child.home = (child.x, child.y);
+        self.energy = self.energy_after_birth(config);
+        partner.energy = partner.energy_after_birth(config);
+
+        child
+    }
+
+    pub fn draw(&self, transform: math::Matrix2d, show_energy_bar: bool, color_mode: ColorMode, g: &mut G2d) {
         let size = self.size();
-        rectangle(
-            self.color,
+        let color = match color_mode {
+            ColorMode::Type => self.color,
+            ColorMode::Energy => {
+                let brightness = energy_brightness(self.energy_ratio());
+                let [red, green, blue, alpha] = self.color;
+                [red * brightness, green * brightness, blue * brightness, alpha]
+            },
+            ColorMode::Age => age_tint(self.color, self.age as f32 / self.max_age.max(1) as f32),
+            ColorMode::Genes => self.genetics.color(),
+        };
+        ellipse(
+            color,
             [self.x, self.y, size, size],
             transform,
             g,
         );
+
+        // Short line from the center showing where the being is heading
+        let (cx, cy) = (self.x + size / 2.0, self.y + size / 2.0);
+        line(
+            [1.0, 1.0, 1.0, 0.8],
+            1.0,
+            [cx, cy, cx + self.vx * size, cy + self.vy * size],
+            transform,
+            g,
+        );
+
+        if self.feed_flash > 0 {
+            Ellipse::new_border([1.0, 1.0, 1.0, 1.0], 1.0)
+                .draw([self.x, self.y, size, size], &DrawState::default(), transform, g);
+        }
+
+        if self.infected {
+            Ellipse::new_border([0.8, 0.1, 0.8, 1.0], 1.0)
+                .draw([self.x - 1.0, self.y - 1.0, size + 2.0, size + 2.0], &DrawState::default(), transform, g);
+        }
+
+        if show_energy_bar {
+            self.draw_energy_bar(transform, g);
+        }
+    }
+
+    // Faint outline of what the being can currently sense, a wedge along its heading
+    // with cone perception and a circle otherwise
+    pub fn draw_perception(&self, tick: u64, config: &Config, transform: math::Matrix2d, g: &mut G2d) {
+        const COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.15];
+
+        let size = self.size();
+        let (cx, cy) = (self.x + size / 2.0, self.y + size / 2.0);
+        let r = self.effective_perception(tick, config);
+        let bounds = [cx - r, cy - r, 2.0 * r, 2.0 * r];
+
+        match config.perception_shape {
+            PerceptionShape::Cone { half_angle } if half_angle < std::f64::consts::PI && (self.vx, self.vy) != (0.0, 0.0) => {
+                let heading = self.vy.atan2(self.vx);
+                let (start, end) = (heading - half_angle, heading + half_angle);
+                CircleArc::new(COLOR, 0.5, start, end).draw(bounds, &DrawState::default(), transform, g);
+                for edge in [start, end] {
+                    line(COLOR, 0.5, [cx, cy, cx + r * edge.cos(), cy + r * edge.sin()], transform, g);
+                }
+            },
+            _ => Ellipse::new_border(COLOR, 0.5).draw(bounds, &DrawState::default(), transform, g),
+        }
+    }
+
+    // Bar above the being filled by energy / max_energy, red when low and green when full
+    fn draw_energy_bar(&self, transform: math::Matrix2d, g: &mut G2d) {
+        const BAR_WIDTH: f64 = 12.0;
+        const BAR_HEIGHT: f64 = 2.0;
+
+        let ratio = self.energy_ratio();
+        let x = self.x + self.size() / 2.0 - BAR_WIDTH / 2.0;
+        let y = (self.y - BAR_HEIGHT - 2.0).max(0.0);  // Stay inside the simulation area
+
+        rectangle([0.3, 0.3, 0.3, 1.0], [x, y, BAR_WIDTH, BAR_HEIGHT], transform, g);
+        rectangle(
+            [1.0 - ratio, ratio, 0.0, 1.0],
+            [x, y, BAR_WIDTH * ratio as f64, BAR_HEIGHT],
+            transform,
+            g,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MutationModel;
+    use crate::simulation::FOOD_GRID_CELL_SIZE;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn being_at(x: f64, y: f64, being_type: BeingType, config: &Config) -> Being {
+        let mut being = Being::with_genetics(x, y, being_type, Genetics::plain(), config);
+        being.energy = 1.0;
+        being
+    }
+
+    fn food_grid(foods: &[Food], config: &Config) -> SpatialGrid {
+        let wrap = config.boundary_mode == BoundaryMode::Wrap;
+        SpatialGrid::from_entities(foods, FOOD_GRID_CELL_SIZE, config.world_width, config.world_height, wrap)
+    }
+
+    #[test]
+    fn newborns_stay_inside_the_world() {
+        for boundary_mode in [BoundaryMode::Clamp, BoundaryMode::Wrap, BoundaryMode::Reflect] {
+            let config = Config { boundary_mode, ..Config::default() };
+            let mut rng = StdRng::seed_from_u64(7);
+            for _ in 0..200 {
+                let mut parent = being_at(0.0, 0.0, BeingType::Herbivore, &config);
+                let mut partner = being_at(config.world_width - 1.0, 0.0, BeingType::Herbivore, &config);
+                let budded = parent.replicate(&config, &mut rng);
+                let mated = parent.mate(&mut partner, &config, &mut rng);
+                assert!(config.in_world(budded.x, budded.y), "{:?} budded at ({}, {})", boundary_mode, budded.x, budded.y);
+                assert!(config.in_world(mated.x, mated.y), "{:?} mated at ({}, {})", boundary_mode, mated.x, mated.y);
+                assert_eq!(budded.home, (budded.x, budded.y));
+            }
+        }
+    }
+
+    #[test]
+    fn breeding_starts_after_min_repro_age() {
+        let config = Config { herbivore_repro_chance: 1000.0, ..Config::default() };
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut being = being_at(400.0, 400.0, BeingType::Herbivore, &config);
+        being.energy = 1.5;
+
+        being.age = config.herbivore_min_repro_age;
+        assert!(!being.can_replicate(&config, &mut rng));
+        being.age += 1;
+        assert!(being.can_replicate(&config, &mut rng));
+    }
+
+    // A StepRng stuck at zero draws the low end of every range, so each gene shrinks by
+    // the full jitter width
+    #[test]
+    #[allow(deprecated)]
+    fn mutate_is_deterministic_with_a_fixed_rng() {
+        let mut rng = rand::rngs::mock::StepRng::new(0, 0);
+        let mutated = Genetics::plain().mutate(1.0, MutationModel::Independent, &mut rng);
+
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+        assert!(close(mutated.speed, 0.9));
+        assert!(close(mutated.size, 0.9));
+        assert!(close(mutated.reproduction_rate, 0.9));
+        assert!(close(mutated.perception, 18.0));
+        assert!(close(mutated.max_energy, 1.8));
+        assert!(close(mutated.metabolism, 0.9));
+        assert!(close(mutated.mutation_strength, 0.9));
+        assert!(close(mutated.diet_preference, 0.63));
+        assert!(mutated == Genetics::plain().mutate(1.0, MutationModel::Independent, &mut rng));
+    }
+
+    #[test]
+    fn distances_cross_the_world_seam() {
+        let clamp = Config::default();
+        let wrap = Config { boundary_mode: BoundaryMode::Wrap, ..Config::default() };
+        let being = being_at(5.0, 400.0, BeingType::Herbivore, &clamp);
+        let other = being_at(795.0, 400.0, BeingType::Herbivore, &clamp);
+        let food = Food { x: 8.0, y: 404.0, energy: 0.5, food_type: FoodType::Plant };
+
+        assert_eq!(being.distance_sq_to(&other, &clamp), 790.0 * 790.0);
+        assert_eq!(being.distance_sq_to(&other, &wrap), 100.0);
+        assert_eq!(being.distance_sq_to(&food, &clamp), 25.0);
+        assert_eq!(being.distance_sq_to(&Corpse { x: 795.0, y: 400.0, energy: 0.1, ticks_left: 1 }, &wrap), 100.0);
+    }
+
+    #[test]
+    fn distance_to_is_the_unsquared_distance() {
+        let clamp = Config::default();
+        let wrap = Config { boundary_mode: BoundaryMode::Wrap, ..Config::default() };
+        let being = being_at(10.0, 20.0, BeingType::Herbivore, &clamp);
+        let other = being_at(790.0, 780.0, BeingType::Herbivore, &clamp);
+        let food = Food { x: 13.0, y: 24.0, energy: 0.5, food_type: FoodType::Plant };
+
+        assert_eq!(being.distance_to(&food, &clamp), 5.0);
+        assert_eq!(being.distance_to(&being, &clamp), 0.0);
+        // 20 across the x seam and 40 across the y seam
+        assert!((being.distance_to(&other, &wrap) - 2000.0_f64.sqrt()).abs() < 1e-9);
+        assert_eq!(being.distance_to(&other, &wrap), other.distance_to(&being, &wrap));
+        assert!(being.distance_to(&other, &clamp) > 1000.0);
+    }
+
+    // The StepRng draws -1 for both axes of a random step, straight away from the food
+    #[test]
+    #[allow(deprecated)]
+    fn full_being_ignores_adjacent_food() {
+        let config = Config::default();
+        let foods = [Food { x: 403.0, y: 400.0, energy: 0.5, food_type: FoodType::Plant }];
+        let grid = food_grid(&foods, &config);
+
+        for (energy, moves_closer) in [(2.0, false), (1.0, true)] {
+            let mut being = being_at(400.0, 400.0, BeingType::Herbivore, &config);
+            being.energy = energy;
+            let mut eaten = Vec::new();
+            being.update_herbivore(&[], &foods, &grid, 20.0, false, &config, &mut rand::rngs::mock::StepRng::new(0, 0), &mut eaten);
+
+            assert_eq!(being.x > 400.0, moves_closer, "energy {}", energy);
+            assert_eq!(eaten.is_empty(), !moves_closer, "energy {}", energy);
+        }
+    }
+
+    #[test]
+    fn eating_stops_at_max_energy() {
+        let config = Config::default();
+        let mut being = being_at(400.0, 400.0, BeingType::Herbivore, &config);
+
+        being.eat(10.0, &config);
+        assert_eq!(being.energy, being.genetics.max_energy);
+        assert_eq!(being.meal_gain(10.0, &config), 0.0);
+    }
+
+    // Seeking heads straight left along y = 400, wandering with the StepRng drifts up
+    #[test]
+    #[allow(deprecated)]
+    fn food_across_the_seam_is_in_sight() {
+        let foods = [Food { x: 795.0, y: 400.0, energy: 0.5, food_type: FoodType::Plant }];
+
+        for (boundary_mode, seeks) in [(BoundaryMode::Wrap, true), (BoundaryMode::Clamp, false)] {
+            let config = Config { boundary_mode, ..Config::default() };
+            let mut being = being_at(5.0, 400.0, BeingType::Herbivore, &config);
+            let mut eaten = Vec::new();
+            being.update_herbivore(&[], &foods, &food_grid(&foods, &config), 20.0, false, &config, &mut rand::rngs::mock::StepRng::new(0, 0), &mut eaten);
+
+            assert!(being.x < 5.0);
+            assert_eq!(being.y == 400.0, seeks, "{:?}", boundary_mode);
+        }
+    }
+
+    #[test]
+    fn prime_beings_are_fastest() {
+        let config = Config::default();
+        let mut being = being_at(400.0, 400.0, BeingType::Herbivore, &config);
+        let max_age = being.max_age;
+        let mut speed_at = |age: u32| {
+            being.age = age;
+            being.speed()
+        };
+
+        let young = speed_at(10);
+        let prime = speed_at(max_age / 2);
+        let old = speed_at(max_age - 1);
+
+        assert!(young < prime, "young {} prime {}", young, prime);
+        assert!(old < prime, "old {} prime {}", old, prime);
+        assert_eq!(prime, 1.0);
+    }
+
+    #[test]
+    fn herbivore_ignores_carrion_underfoot() {
+        let config = Config::default();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for (food_type, eats) in [(FoodType::Carrion, false), (FoodType::Plant, true)] {
+            let foods = [Food { x: 400.0, y: 400.0, energy: 0.5, food_type }];
+            let mut being = being_at(400.0, 400.0, BeingType::Herbivore, &config);
+            let mut eaten = Vec::new();
+            being.update_herbivore(&[], &foods, &food_grid(&foods, &config), 20.0, false, &config, &mut rng, &mut eaten);
+
+            assert_eq!(!eaten.is_empty(), eats, "{:?}", food_type);
+        }
+    }
+
+    #[test]
+    fn meal_gain_follows_metabolism() {
+        let config = Config::default();
+        let mut rng = StdRng::seed_from_u64(3);
+        let samples: Vec<(f64, f64)> = (0..200)
+            .map(|_| {
+                let genetics = Genetics::new_random(BeingType::Herbivore, &mut rng);
+                let mut being = Being::with_genetics(400.0, 400.0, BeingType::Herbivore, genetics, &config);
+                being.energy = 0.5;
+                being.eat(0.3, &config);
+                (being.genetics.metabolism as f64, being.energy as f64 - 0.5)
+            })
+            .collect();
+
+        let n = samples.len() as f64;
+        let (mean_m, mean_g) = samples.iter().fold((0.0, 0.0), |(m, g), &(a, b)| (m + a / n, g + b / n));
+        let covariance: f64 = samples.iter().map(|&(m, g)| (m - mean_m) * (g - mean_g)).sum();
+        let spread = |f: fn(&(f64, f64)) -> f64, mean: f64| samples.iter().map(|s| (f(s) - mean).powi(2)).sum::<f64>().sqrt();
+        let correlation = covariance / (spread(|s| s.0, mean_m) * spread(|s| s.1, mean_g));
+
+        assert!(correlation > 0.9, "correlation {}", correlation);
+    }
+
+    // A foraging omnivore heads straight for the food, wandering with the StepRng
+    // would drift away from it
+    #[test]
+    #[allow(deprecated)]
+    fn omnivore_walks_straight_to_food() {
+        let config = Config::default();
+        let foods = [Food { x: 406.0, y: 400.0, energy: 0.5, food_type: FoodType::Plant }];
+        let mut being = being_at(400.0, 400.0, BeingType::Omnivore, &config);
+        being.genetics.diet_preference = 0.0;
+
+        let (prey, eaten) = being.update_omnivore(&[], &foods, &food_grid(&foods, &config), 20.0, &config, &mut rand::rngs::mock::StepRng::new(0, 0));
+
+        assert_eq!((prey, eaten), (None, vec![0]));
+        assert!(being.x > 400.0);
+        assert_eq!(being.y, 400.0);
+    }
+
+    #[test]
+    fn repro_cost_can_rule_out_breeding() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for (repro_cost, allowed) in [(0.0, true), (0.5, false)] {
+            let config = Config { herbivore_repro_chance: 1000.0, repro_cost, ..Config::default() };
+            let mut being = being_at(400.0, 400.0, BeingType::Herbivore, &config);
+            being.age = config.herbivore_min_repro_age + 1;
+            being.energy = config.herbivore_min_repro_energy + 0.01;
+
+            assert_eq!(being.can_replicate(&config, &mut rng), allowed, "repro_cost {}", repro_cost);
+        }
+    }
+
+    #[test]
+    fn night_shortens_perception_except_for_carnivores() {
+        let config = Config::default();
+        let (noon, midnight) = (0, config.day_length / 2);
+        let mut herbivore = being_at(400.0, 400.0, BeingType::Herbivore, &config);
+        let mut carnivore = being_at(400.0, 400.0, BeingType::Carnivore, &config);
+        herbivore.age = 100;
+        carnivore.age = 100;
+
+        assert!(herbivore.effective_perception(midnight, &config) < herbivore.effective_perception(noon, &config));
+        assert_eq!(carnivore.effective_perception(midnight, &config), carnivore.effective_perception(noon, &config));
+    }
+
+    #[test]
+    fn flock_pulls_toward_the_centroid() {
+        let config = Config::default();
+        let flock: Vec<Being> = [(380.0, 400.0), (420.0, 400.0), (400.0, 430.0)].iter()
+            .enumerate()
+            .map(|(i, &(x, y))| {
+                let mut being = being_at(x, y, BeingType::Herbivore, &config);
+                being.id = i as u64 + 1;
+                being
+            })
+            .collect();
+        let neighbors: Vec<(usize, &Being)> = flock.iter().enumerate().collect();
+        let centroid = Vec2::new(400.0, 410.0);
+
+        for being in &flock {
+            let force = being.flock_force(&neighbors, &config);
+            let toward = centroid - being.pos();
+            assert!(force.x * toward.x + force.y * toward.y > 0.0, "being {} pulled {:?}", being.id, (force.x, force.y));
+        }
+    }
+
+    #[test]
+    fn reflect_bounces_off_the_right_edge() {
+        let config = Config { boundary_mode: BoundaryMode::Reflect, ..Config::default() };
+        let mut being = being_at(0.0, 400.0, BeingType::Herbivore, &config);
+        let edge = config.world_width - being.size();
+        being.x = edge - 1.0;
+        let start = being.pos();
+
+        being.move_by(Vec2::new(3.0, 0.0));
+        being.update_heading(start);
+        being.settle(start, &config);
+
+        assert_eq!(being.x, edge - 2.0);
+        assert!(being.vx < 0.0);
+    }
+
+    #[test]
+    fn well_fed_beings_breed_more_often() {
+        let config = Config { herbivore_repro_chance: 0.05, ..Config::default() };
+        let mut rng = StdRng::seed_from_u64(6);
+        let mut births = |energy| {
+            let mut being = being_at(400.0, 400.0, BeingType::Herbivore, &config);
+            being.age = config.herbivore_min_repro_age + 1;
+            being.energy = energy;
+            (0..20_000).filter(|_| being.can_replicate(&config, &mut rng)).count()
+        };
+
+        let lean = births(0.85);
+        let fat = births(1.5);
+
+        assert!(lean > 0);
+        assert!(fat as f32 > lean as f32 * 1.4, "0.85 bred {} times, 1.5 bred {} times", lean, fat);
+    }
+
+    #[test]
+    fn being_types_parse_what_they_print() {
+        for being_type in BeingType::ALL {
+            assert_eq!(being_type.to_string().parse::<BeingType>(), Ok(being_type));
+            assert_eq!(being_type.to_string().to_uppercase().parse::<BeingType>(), Ok(being_type));
+        }
+
+        let err = "dragon".parse::<BeingType>().unwrap_err();
+        assert!(err.contains("\"dragon\"") && err.contains("herbivore"), "{}", err);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn carnivore_far_from_home_is_pulled_back() {
+        let wander = |territory_radius| {
+            let config = Config { territory_radius, ..Config::default() };
+            let mut being = being_at(400.0, 400.0, BeingType::Carnivore, &config);
+            being.home = (600.0, 400.0);
+            being.energy = 2.0;  // Full, so it patrols instead of hunting
+            assert_eq!(being.home_pull(&config).x, if territory_radius > 0.0 { 1.0 } else { 0.0 });
+            being.update_carnivore(&[], 20.0, false, &config, &mut rand::rngs::mock::StepRng::new(0, 0));
+            being.x
+        };
+
+        assert!(wander(100.0) > wander(0.0));
+    }
+
+    #[test]
+    fn brightness_dims_with_energy_but_stays_visible() {
+        assert_eq!(energy_brightness(1.0), 1.0);
+        assert_eq!(energy_brightness(0.0), MIN_BRIGHTNESS);
+        assert_eq!(energy_brightness(-3.0), MIN_BRIGHTNESS);
+        assert!(energy_brightness(0.3) < energy_brightness(0.6));
+    }
+
+    #[test]
+    fn age_tint_grays_out_toward_max_age() {
+        let red = [1.0, 0.0, 0.0, 1.0];
+
+        assert_eq!(age_tint(red, 0.0), red);
+        assert_eq!(age_tint(red, 1.0), [0.5, 0.5, 0.5, 1.0]);
+        let old = age_tint(red, 0.9);
+        assert!(old[0] - 0.5 < 0.1 && 0.5 - old[1] < 0.1, "{:?}", old);
+    }
+
+    #[test]
+    fn larger_prey_ratio_brings_bigger_beings_on_the_menu() {
+        let caught = |carnivore_prey_size_ratio| {
+            let config = Config { carnivore_prey_size_ratio, ..Config::default() };
+            let mut hunter = being_at(400.0, 400.0, BeingType::Carnivore, &config);
+            let mut prey = being_at(402.0, 400.0, BeingType::Herbivore, &config);
+            prey.id = 1;
+            prey.genetics.size = 1.3;
+            hunter.update_carnivore(&[(0, &prey)], 20.0, false, &config, &mut StdRng::seed_from_u64(1))
+        };
+
+        assert_eq!(caught(1.1), None);
+        assert_eq!(caught(1.5), Some(0));
+    }
+
+    #[test]
+    fn sub_viable_child_is_not_budded() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for (min_viable_energy, allowed) in [(0.3, true), (0.5, false)] {
+            let config = Config { herbivore_repro_chance: 1000.0, repro_cost: 0.0, min_viable_energy, ..Config::default() };
+            let mut being = being_at(400.0, 400.0, BeingType::Herbivore, &config);
+            being.age = config.herbivore_min_repro_age + 1;
+            being.energy = 0.85;  // Would bud a child with 0.425
+
+            assert_eq!(being.can_replicate(&config, &mut rng), allowed, "min_viable_energy {}", min_viable_energy);
+        }
+    }
+
+    #[test]
+    fn second_meal_in_a_row_yields_less() {
+        let config = Config { digestion_limit: true, ..Config::default() };
+        let mut being = being_at(400.0, 400.0, BeingType::Herbivore, &config);
+        being.energy = 0.2;
+
+        being.eat(0.3, &config);
+        let first = being.energy - 0.2;
+        let before = being.energy;
+        being.eat(0.3, &config);
+        let second = being.energy - before;
+
+        assert!(second < first, "first {} second {}", first, second);
+        assert!((second - first / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn pure_forager_never_hunts() {
+        let config = Config::default();
+        let foods = [Food { x: 410.0, y: 400.0, energy: 0.5, food_type: FoodType::Plant }];
+        let grid = food_grid(&foods, &config);
+        let mut prey = being_at(401.0, 400.0, BeingType::Herbivore, &config);
+        prey.id = 1;
+        prey.genetics.size = 0.5;
+        let mut rng = StdRng::seed_from_u64(8);
+
+        for _ in 0..200 {
+            let mut omnivore = being_at(400.0, 400.0, BeingType::Omnivore, &config);
+            omnivore.genetics.diet_preference = 0.0;
+            let (caught, _) = omnivore.update_omnivore(&[(0, &prey)], &foods, &grid, 20.0, &config, &mut rng);
+
+            assert_eq!(caught, None);
+            assert!(omnivore.x > 400.0);
+        }
+    }
+
+    #[test]
+    fn predation_efficiency_scales_the_kill() {
+        let mut prey = being_at(400.0, 400.0, BeingType::Herbivore, &Config::default());
+        prey.energy = 0.8;
+
+        for (efficiency, value) in [(0.95, 0.76), (0.5, 0.4)] {
+            let config = Config { carnivore_predation_efficiency: efficiency, omnivore_predation_efficiency: efficiency, ..Config::default() };
+            let mut hunter = being_at(400.0, 400.0, BeingType::Carnivore, &config);
+            hunter.energy = 0.5;
+            assert!((hunter.prey_value(&prey, &config) - value).abs() < 1e-6);
+            assert!((hunter.meal_gain(hunter.prey_value(&prey, &config), &config) - value).abs() < 1e-6);
+
+            let omnivore = being_at(400.0, 400.0, BeingType::Omnivore, &config);
+            assert!((omnivore.prey_value(&prey, &config) - value).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn cone_sees_ahead_but_not_behind() {
+        let config = Config { perception_shape: PerceptionShape::Cone { half_angle: std::f64::consts::FRAC_PI_4 }, ..Config::default() };
+        let mut being = being_at(400.0, 400.0, BeingType::Carnivore, &config);
+        // A slow heading still covers the full cone
+        (being.vx, being.vy) = (0.3, 0.0);
+
+        assert!(being.in_view(Vec2::new(10.0, 0.0), &config));
+        assert!(being.in_view(Vec2::new(10.0, 5.0), &config));
+        assert!(!being.in_view(Vec2::new(10.0, 15.0), &config));
+        assert!(!being.in_view(Vec2::new(-10.0, 0.0), &config));
+    }
+
+    // Seeking food keeps y fixed, the StepRng wander doesn't
+    #[test]
+    #[allow(deprecated)]
+    fn cone_perception_ignores_food_directly_behind() {
+        let cone = PerceptionShape::Cone { half_angle: std::f64::consts::FRAC_PI_4 };
+        for (perception_shape, food_x, seeks) in [(cone, 385.0, false), (PerceptionShape::Circle, 385.0, true), (cone, 415.0, true)] {
+            let config = Config { perception_shape, ..Config::default() };
+            let foods = [Food { x: food_x, y: 400.0, energy: 0.5, food_type: FoodType::Plant }];
+            let mut being = being_at(400.0, 400.0, BeingType::Herbivore, &config);
+            (being.vx, being.vy) = (1.0, 0.0);
+            let mut eaten = Vec::new();
+            being.update_herbivore(&[], &foods, &food_grid(&foods, &config), 20.0, false, &config, &mut rand::rngs::mock::StepRng::new(0, 0), &mut eaten);
+
+            assert_eq!(being.y == 400.0, seeks, "{:?} with food at x={}", perception_shape, food_x);
+        }
+    }
+
+    #[test]
+    fn newborn_keeps_clear_of_a_parent_in_the_corner() {
+        for boundary_mode in [BoundaryMode::Clamp, BoundaryMode::Wrap, BoundaryMode::Reflect] {
+            let config = Config { boundary_mode, ..Config::default() };
+            let mut rng = StdRng::seed_from_u64(3);
+            for _ in 0..200 {
+                let mut parent = being_at(0.0, 0.0, BeingType::Herbivore, &config);
+                parent.energy = 2.0;
+                let child = parent.replicate(&config, &mut rng);
+                let distance = child.distance_to(&parent, &config);
+                assert!(distance >= parent.size(), "{:?} child at ({}, {}) only {} away", boundary_mode, child.x, child.y, distance);
+            }
+        }
+    }
+
+    // Prey half a hunter's size gives a lone reach of 7.5, each ally adds half of that
+    #[test]
+    fn pack_catches_prey_from_further_away() {
+        let config = Config { pack_bonus: 0.5, ..Config::default() };
+        let caught = |prey_distance: f64, allies: usize| {
+            let mut hunter = being_at(400.0, 400.0, BeingType::Carnivore, &config);
+            hunter.id = 1;
+            let mut prey = being_at(400.0 + prey_distance, 400.0, BeingType::Herbivore, &config);
+            prey.id = 2;
+            prey.genetics.size = 0.5;
+            let pack: Vec<Being> = (0..allies).map(|i| {
+                let mut ally = being_at(400.0 + prey_distance, 410.0, BeingType::Carnivore, &config);
+                ally.id = 3 + i as u64;
+                ally
+            }).collect();
+            let mut beings = vec![(0, &prey)];
+            beings.extend(pack.iter().enumerate().map(|(i, ally)| (i + 1, ally)));
+            hunter.update_carnivore(&beings, 20.0, false, &config, &mut StdRng::seed_from_u64(1))
+        };
+
+        assert_eq!(caught(10.0, 0), None);
+        assert_eq!(caught(10.0, 1), Some(0));
+        // Three allies reach 18.75, more don't stretch it to the 22.5 four would give
+        assert_eq!(caught(18.0, 3), Some(0));
+        assert_eq!(caught(20.0, 5), None);
     }
 }
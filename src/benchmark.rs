@@ -0,0 +1,64 @@
+use serde::Serialize;
+use std::time::Instant;
+
+use crate::being::BeingType;
+use crate::config::{Config, InitialPopulation};
+use crate::simulation::Simulation;
+
+const WARMUP_TICKS: u64 = 300;  // Long enough for births and deaths to settle into a steady state
+
+#[derive(Debug, Serialize)]
+pub struct BenchmarkResult {
+    pub seed: u64,
+    pub warmup_ticks: u64,
+    pub ticks: u64,
+    pub seconds: f64,
+    pub ticks_per_second: f64,
+    pub avg_tick_ms: f64,
+    pub start_population: usize,  // After the warm-up
+    pub end_population: usize,
+    pub threads: usize,
+}
+
+// Times `ticks` headless ticks after a warm-up. Unless the config says otherwise the
+// world starts full, max_beings spread evenly over the types, to stress the hot path
+pub fn run_benchmark(mut config: Config, seed: u64, ticks: u64) -> BenchmarkResult {
+    if config.initial.is_none() {
+        config.initial = Some(full_population(config.max_beings));
+    }
+
+    let mut sim = Simulation::new(config, seed);
+    for _ in 0..WARMUP_TICKS {
+        sim.tick();
+    }
+
+    let start_population = sim.beings.len();
+    let start = Instant::now();
+    for _ in 0..ticks {
+        sim.tick();
+    }
+    let seconds = start.elapsed().as_secs_f64();
+
+    BenchmarkResult {
+        seed,
+        warmup_ticks: WARMUP_TICKS,
+        ticks,
+        seconds,
+        ticks_per_second: ticks as f64 / seconds,
+        avg_tick_ms: seconds * 1000.0 / ticks.max(1) as f64,
+        start_population,
+        end_population: sim.beings.len(),
+        threads: rayon::current_num_threads(),
+    }
+}
+
+fn full_population(max_beings: usize) -> InitialPopulation {
+    let share = max_beings / BeingType::ALL.len();
+    InitialPopulation {
+        herbivore: max_beings - share * (BeingType::ALL.len() - 1),
+        carnivore: share,
+        omnivore: share,
+        scavenger: share,
+        plant: share,
+    }
+}
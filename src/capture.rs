@@ -0,0 +1,78 @@
+use image::RgbaImage;
+use piston_window::{PistonWindow, Window};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Saves rendered frames as numbered PNGs, F12 for a single one or --capture-every
+// for a sequence to assemble into a GIF. The pixels are read back from the GL
+// framebuffer right after drawing, before the buffers are swapped
+pub struct FrameCapture {
+    dir: PathBuf,
+    next_index: u32,
+}
+
+impl FrameCapture {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FrameCapture { dir: dir.into(), next_index: 0 }
+    }
+
+    // Frames of earlier runs in the same directory are left alone
+    pub fn next_path(&mut self) -> PathBuf {
+        loop {
+            let path = frame_path(&self.dir, self.next_index);
+            self.next_index += 1;
+            if !path.exists() {
+                return path;
+            }
+        }
+    }
+
+    pub fn save(&mut self, window: &mut PistonWindow) -> Result<PathBuf, String> {
+        fs::create_dir_all(&self.dir).map_err(|err| err.to_string())?;
+        let path = self.next_path();
+        read_frame(window).save(&path).map_err(|err| err.to_string())?;
+        Ok(path)
+    }
+}
+
+pub fn frame_path(dir: &Path, index: u32) -> PathBuf {
+    dir.join(format!("frame_{:04}.png", index))
+}
+
+fn read_frame(window: &mut PistonWindow) -> RgbaImage {
+    let size = window.draw_size();
+    let (width, height) = (size.width as u32, size.height as u32);
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+
+    // Safe as long as the buffer holds width * height tightly packed RGBA pixels
+    unsafe {
+        window.device.with_gl(|gl| {
+            gl.PixelStorei(gfx_gl::PACK_ALIGNMENT, 1);
+            gl.ReadPixels(0, 0, width as i32, height as i32, gfx_gl::RGBA, gfx_gl::UNSIGNED_BYTE, pixels.as_mut_ptr().cast());
+        });
+    }
+
+    // GL rows start at the bottom
+    let mut frame = RgbaImage::from_raw(width, height, pixels).unwrap_or_else(|| RgbaImage::new(width, height));
+    image::imageops::flip_vertical_in_place(&mut frame);
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_numbered_past_existing_ones() {
+        let dir = std::env::temp_dir().join(format!("simple-life-frames-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(frame_path(&dir, 1), b"").unwrap();
+        let mut capture = FrameCapture::new(&dir);
+
+        let paths = [capture.next_path(), capture.next_path(), capture.next_path()];
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(frame_path(Path::new("shots"), 7), Path::new("shots/frame_0007.png"));
+        assert_eq!(paths, [frame_path(&dir, 0), frame_path(&dir, 2), frame_path(&dir, 3)]);
+    }
+}
@@ -0,0 +1,137 @@
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+use std::process;
+use std::str::FromStr;
+
+use crate::being::BeingType;
+
+#[derive(Default)]
+pub struct CliArgs {
+    pub seed: Option<u64>,
+    pub load: Option<PathBuf>,
+    pub config: Option<PathBuf>,
+    pub headless: bool,
+    pub verbose: bool,
+    pub benchmark: bool,
+    pub ticks: Option<u64>,
+    pub max_ticks: Option<u64>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub stats_port: Option<u16>,
+    pub sweep: Vec<PathBuf>,
+    pub spawn_type: Option<BeingType>,
+    pub font: Option<PathBuf>,
+    pub genomes: Option<PathBuf>,
+    pub events: Option<PathBuf>,
+    pub capture_every: Option<u64>,
+    pub audio: bool,
+}
+
+const USAGE: &str = "\
+Usage: simple-life [options]
+  --seed N              Seed for a reproducible run (or LIFE_SEED)
+  --load FILE           Resume from a saved snapshot
+  --config FILE         Read settings from a TOML file
+  --headless            Run without a window and print a summary
+  --ticks N             Ticks to run headless or in a benchmark
+  --max-ticks N         Close the window after N ticks
+  --benchmark           Time the simulation without rendering
+  --verbose             Trace what every being does to stderr
+  --record FILE         Record the run for replay
+  --replay FILE         Replay a recorded run
+  --sweep A,B,...       Run headless once per config file
+  --stats-port PORT     Serve live stats over TCP
+  --genomes FILE        Export the genomes on exit
+  --events FILE         Write every event as a JSON line
+  --capture-every N     Save every Nth rendered frame
+  --font FILE           Font for the on-screen text
+  --spawn-type TYPE     Type placed by a mouse click
+  --audio               Sound effects, Linux only with aplay and the audio feature";
+
+impl CliArgs {
+    // A malformed value ends the program with the usage text rather than being ignored
+    pub fn parse() -> Self {
+        CliArgs::parse_from(env::args().skip(1)).unwrap_or_else(|err| {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        })
+    }
+
+    fn parse_from(mut iter: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut args = CliArgs::default();
+
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--seed" => args.seed = Some(value(&arg, iter.next())?),
+                "--load" => args.load = Some(value(&arg, iter.next())?),
+                "--config" => args.config = Some(value(&arg, iter.next())?),
+                "--headless" => args.headless = true,
+                "--verbose" => args.verbose = true,
+                "--benchmark" => args.benchmark = true,
+                "--audio" => args.audio = true,
+                "--ticks" => args.ticks = Some(value(&arg, iter.next())?),
+                "--max-ticks" => args.max_ticks = Some(value(&arg, iter.next())?),
+                "--record" => args.record = Some(value(&arg, iter.next())?),
+                "--replay" => args.replay = Some(value(&arg, iter.next())?),
+                "--sweep" => args.sweep = value::<String>(&arg, iter.next())?.split(',').map(PathBuf::from).collect(),
+                "--stats-port" => args.stats_port = Some(value(&arg, iter.next())?),
+                "--genomes" => args.genomes = Some(value(&arg, iter.next())?),
+                "--events" => args.events = Some(value(&arg, iter.next())?),
+                "--capture-every" => args.capture_every = Some(value(&arg, iter.next())?),
+                "--font" => args.font = Some(value(&arg, iter.next())?),
+                "--spawn-type" => args.spawn_type = Some(value(&arg, iter.next())?),
+                _ => eprintln!("Ignoring unknown argument {:?}", arg),
+            }
+        }
+
+        // Fall back to the environment when no flag was given
+        if args.seed.is_none() && let Ok(seed) = env::var("LIFE_SEED") {
+            args.seed = Some(value("LIFE_SEED", Some(seed))?);
+        }
+
+        Ok(args)
+    }
+}
+
+fn value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String>
+where
+    T::Err: fmt::Display,
+{
+    let value = value.ok_or_else(|| format!("{} needs a value", flag))?;
+    value.parse().map_err(|err| format!("invalid value {:?} for {}: {}", value, flag, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_numeric_flags() {
+        let args = parse(&["--seed", "42", "--ticks", "500", "--headless"]).expect("valid arguments");
+        assert_eq!(args.seed, Some(42));
+        assert_eq!(args.ticks, Some(500));
+        assert!(args.headless);
+    }
+
+    #[test]
+    fn rejects_malformed_values() {
+        assert!(parse(&["--ticks", "10k"]).is_err());
+        assert!(parse(&["--stats-port", "70000"]).is_err());
+        assert!(parse(&["--spawn-type", "dragon"]).is_err());
+        assert!(parse(&["--seed"]).is_err());
+    }
+
+    #[test]
+    fn max_ticks_is_separate_from_headless_ticks() {
+        let args = parse(&["--max-ticks", "300"]).expect("valid arguments");
+        assert_eq!(args.max_ticks, Some(300));
+        assert_eq!(args.ticks, None);
+        assert!(!args.headless);
+        assert!(parse(&["--max-ticks", "-1"]).is_err());
+    }
+}
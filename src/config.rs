@@ -0,0 +1,436 @@
+use rand::Rng;
+use rand::distr::Distribution;
+use rand::distr::weighted::WeightedIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::being::BeingType;
+use crate::genetics::{GenePreset, Genetics};
+use crate::wall::Wall;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReproductionMode {
+    Asexual,
+    Sexual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BoundaryMode {
+    Clamp,
+    Wrap,  // Toroidal world, leaving one edge enters from the opposite one
+    Reflect,  // Beings bounce off the edges and turn around
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FoodDistribution {
+    Uniform,
+    Clustered,  // Around a few slowly drifting resource centers
+}
+
+// How a being's upkeep grows with its size, speed always adds linearly. All three
+// agree at size 1, they differ in how hard they punish big bodies
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetabolicModel {
+    Linear,
+    Exponential,  // e^(size - 1)
+    PowerLaw,  // size^1.5, close to Kleiber's law
+}
+
+// Whether genes jitter on their own when passed on, or some move together
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MutationModel {
+    Independent,
+    Linked,  // Size and max_energy share one jitter factor
+}
+
+// Who goes when the population is over max_beings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CullPolicy {
+    Tail,  // Drop the end of the list, which is mostly newborns
+    LowestEnergy,
+    Random,
+}
+
+// Where a being can see targets, always limited by its perception range
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PerceptionShape {
+    Circle,
+    Cone { half_angle: f64 },  // Radians either side of the heading
+}
+
+// How many beings of each type start at random spots
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InitialPopulation {
+    pub herbivore: usize,
+    pub carnivore: usize,
+    pub omnivore: usize,
+    pub scavenger: usize,
+    pub plant: usize,
+}
+
+impl InitialPopulation {
+    pub fn get(&self, being_type: BeingType) -> usize {
+        match being_type {
+            BeingType::Herbivore => self.herbivore,
+            BeingType::Carnivore => self.carnivore,
+            BeingType::Omnivore => self.omnivore,
+            BeingType::Scavenger => self.scavenger,
+            BeingType::Plant => self.plant,
+        }
+    }
+}
+
+// One option for an initial being's genes, picked with odds proportional to the weight
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightedPreset {
+    pub preset: Option<String>,  // Name in gene_presets, left out for random genes
+    pub weight: f64,
+}
+
+// Gene presets the initial beings of each type pick from, empty means random genes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InitialGenes {
+    pub herbivore: Vec<WeightedPreset>,
+    pub carnivore: Vec<WeightedPreset>,
+    pub omnivore: Vec<WeightedPreset>,
+    pub scavenger: Vec<WeightedPreset>,
+    pub plant: Vec<WeightedPreset>,
+}
+
+impl InitialGenes {
+    pub fn get(&self, being_type: BeingType) -> &[WeightedPreset] {
+        match being_type {
+            BeingType::Herbivore => &self.herbivore,
+            BeingType::Carnivore => &self.carnivore,
+            BeingType::Omnivore => &self.omnivore,
+            BeingType::Scavenger => &self.scavenger,
+            BeingType::Plant => &self.plant,
+        }
+    }
+}
+
+impl MetabolicModel {
+    // Factor the energy decay is scaled by
+    pub fn load(self, size: f32, speed: f32) -> f32 {
+        let size_cost = match self {
+            MetabolicModel::Linear => size,
+            MetabolicModel::Exponential => (size - 1.0).exp(),
+            MetabolicModel::PowerLaw => size.powf(1.5),
+        };
+        size_cost + speed
+    }
+}
+
+impl BoundaryMode {
+    // Signed offset from `from` to `to` along an axis of length `extent`,
+    // taking the shorter way across the seam when wrapping
+    pub fn delta(self, from: f64, to: f64, extent: f64) -> f64 {
+        let d = to - from;
+        match self {
+            BoundaryMode::Clamp | BoundaryMode::Reflect => d,
+            BoundaryMode::Wrap => d - extent * (d / extent).round(),
+        }
+    }
+}
+
+// Every tuning knob of the simulation, missing TOML keys keep the default
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub world_width: f64,
+    pub world_height: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial: Option<InitialPopulation>,  // None keeps the classic fixed starting layout
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub gene_presets: BTreeMap<String, GenePreset>,
+    pub initial_genes: InitialGenes,
+    pub max_beings: usize,
+    pub max_food: usize,
+    pub food_spawn_rate: f64,
+    pub food_per_tick: f64,  // Food grown every tick regardless of chance, fractions carry over. 0 uses food_spawn_rate
+    pub day_length: u64,  // Ticks per day/night cycle, 0 means permanent daylight
+    pub temperature_effect: f32,  // How much local temperature scales energy decay, 0 gives every region the same climate
+    pub biome_cell_size: f64,  // Distance between the random temperature points of the biome grid
+    pub season_length: u64,  // Ticks per full season cycle, 0 turns seasons off
+    pub season_amplitude: f64,  // How far the spawn rate swings, as a fraction of food_spawn_rate
+    pub epoch_length: u64,  // Ticks per stats epoch with its own birth, death and meal counts, 0 disables epochs
+    pub carrying_capacity: usize,  // Herbivore count at which food stops growing back, 0 disables the feedback
+    pub food_distribution: FoodDistribution,
+    pub food_clusters: usize,  // Number of resource centers when clustered
+    pub food_cluster_spread: f64,  // Standard deviation of spawns around a center
+    pub food_cluster_drift: f64,  // Max distance a center moves per tick
+    pub energy_decay: f32,
+    pub metabolic_model: MetabolicModel,
+    pub base_being_size: f64,
+    pub crowding_penalty: f32,  // Energy lost per tick for each same-type being within body size
+    pub edge_margin: f64,  // Width of the band along the world edges that drains energy, 0 disables it
+    pub edge_drain: f32,  // Extra energy lost per tick inside that band
+    pub min_separation: f64,  // Closest two beings may get, as a fraction of their summed radii. 0 lets them overlap freely
+    pub flocking_weight: f64,  // How strongly herbivores flock, as a fraction of their speed. 0 disables it
+    pub pheromone_deposit: f32,  // Trail each herbivore leaves per tick, 0 disables trails
+    pub pheromone_decay: f32,  // Fraction of the trail that fades every tick
+    pub pheromone_weight: f64,  // How strongly herbivores follow trails, as a fraction of their speed
+    pub hunger_threshold: f32, // Fraction of a being's max_energy
+    pub starvation_threshold: f32, // Fraction of max_energy below which a being is starving
+    pub allow_cannibalism: bool,  // Starving carnivores may hunt weaker carnivores
+    pub digestion_limit: bool,  // Meals in quick succession give diminishing energy
+    pub digestion_rate: f32,  // Fraction of the recent meal count digested per tick
+    pub carnivore_prey_size_ratio: f64,  // Largest prey a carnivore goes after, relative to its own size
+    pub omnivore_prey_size_ratio: f64,
+    pub carnivore_predation_efficiency: f32,  // Share of a caught prey's energy the carnivore gets
+    pub omnivore_predation_efficiency: f32,
+    // Multiplier on the energy omnivores get from food. Above 1 it hands out more energy
+    // than the food held, which the energy audit books as "food eaten"
+    pub omnivore_forage_bonus: f32,
+    pub pack_bonus: f64,  // Extra catch reach per fellow carnivore near the same prey, as a fraction of the normal reach. 0 disables pack hunting
+    pub territory_radius: f64,  // Carnivores patrol this far around their birthplace and attack intruders there. 0 disables territories
+    pub reproduction_mode: ReproductionMode,
+    pub mating_radius: f64,
+    pub repro_cost: f32,  // Flat energy a parent loses per birth on top of the child's share
+    pub repro_survival_floor: f32,  // Parents never breed if that would leave them below this
+    pub min_viable_energy: f32,  // No birth happens if the child would start with less energy than this
+    pub mutation_rate: f32,  // Scales how far offspring genes stray from their parents, 0 disables mutation
+    pub mutation_model: MutationModel,
+    pub boundary_mode: BoundaryMode,
+    pub perception_shape: PerceptionShape,
+    pub cull_policy: CullPolicy,
+    pub herbivore_max_age: u32,
+    pub carnivore_max_age: u32,
+    pub omnivore_max_age: u32,
+    pub scavenger_max_age: u32,
+    pub plant_max_age: u32,
+    pub herbivore_repro_chance: f32,
+    pub carnivore_repro_chance: f32,
+    pub omnivore_repro_chance: f32,
+    pub scavenger_repro_chance: f32,
+    pub plant_repro_chance: f32,
+    pub herbivore_min_repro_age: u32,  // A being has to be older than this to breed
+    pub carnivore_min_repro_age: u32,
+    pub omnivore_min_repro_age: u32,
+    pub scavenger_min_repro_age: u32,
+    pub plant_min_repro_age: u32,
+    pub herbivore_min_repro_energy: f32,  // Energy a being needs before it can breed
+    pub carnivore_min_repro_energy: f32,
+    pub omnivore_min_repro_energy: f32,
+    pub scavenger_min_repro_energy: f32,
+    pub plant_min_repro_energy: f32,
+    pub plant_photosynthesis: f32,  // Energy a plant gains per tick at full daylight
+    pub disease_outbreak_chance: f64,  // Per tick chance that a random being falls ill, 0 keeps the world disease-free
+    pub disease_transmission: f32,  // Chance per tick and infected contact to catch the disease
+    pub disease_duration: u32,  // Ticks until an infection clears or kills
+    pub disease_lethality: f32,  // Chance the infection kills when it runs its course
+    pub disease_decay: f32,  // Extra energy an infected being loses per tick
+    pub corpse_lifetime: u32,  // Ticks a corpse stays before rotting away
+    pub corpse_energy: f32,  // Corpse energy per unit of body size
+    pub walls: Vec<Wall>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            world_width: 800.0,
+            world_height: 800.0,
+            initial: None,
+            gene_presets: BTreeMap::new(),
+            initial_genes: InitialGenes::default(),
+            max_beings: 220,
+            max_food: 790,
+            food_spawn_rate: 0.99,
+            food_per_tick: 0.0,
+            day_length: 2400,
+            temperature_effect: 0.0,
+            biome_cell_size: 200.0,
+            season_length: 4000,
+            season_amplitude: 0.3,
+            carrying_capacity: 0,
+            epoch_length: 0,
+            food_distribution: FoodDistribution::Uniform,
+            food_clusters: 4,
+            food_cluster_spread: 60.0,
+            food_cluster_drift: 0.3,
+            energy_decay: 0.0000015,
+            metabolic_model: MetabolicModel::Linear,
+            base_being_size: 10.0,
+            crowding_penalty: 0.0,
+            edge_margin: 0.0,
+            edge_drain: 0.0002,
+            min_separation: 0.0,
+            flocking_weight: 0.0,
+            pheromone_deposit: 0.0,
+            pheromone_decay: 0.01,
+            pheromone_weight: 0.3,
+            hunger_threshold: 0.8,
+            starvation_threshold: 0.25,
+            allow_cannibalism: false,
+            digestion_limit: false,
+            digestion_rate: 0.02,
+            territory_radius: 0.0,
+            carnivore_prey_size_ratio: 1.1,
+            omnivore_prey_size_ratio: 0.9,
+            carnivore_predation_efficiency: 0.95,
+            omnivore_predation_efficiency: 0.85,
+            omnivore_forage_bonus: 1.2,
+            pack_bonus: 0.0,
+            reproduction_mode: ReproductionMode::Asexual,
+            mating_radius: 20.0,
+            repro_cost: 0.05,
+            repro_survival_floor: 0.2,
+            min_viable_energy: 0.0,
+            mutation_rate: 1.0,
+            mutation_model: MutationModel::Independent,
+            boundary_mode: BoundaryMode::Clamp,
+            perception_shape: PerceptionShape::Circle,
+            cull_policy: CullPolicy::LowestEnergy,
+            herbivore_max_age: 3000,
+            carnivore_max_age: 2000,
+            omnivore_max_age: 2500,
+            scavenger_max_age: 2500,
+            plant_max_age: 4000,
+            herbivore_repro_chance: 0.0011,
+            carnivore_repro_chance: 0.0016,
+            omnivore_repro_chance: 0.0013,
+            scavenger_repro_chance: 0.0012,
+            plant_repro_chance: 0.002,
+            herbivore_min_repro_age: 80,
+            carnivore_min_repro_age: 80,
+            omnivore_min_repro_age: 80,
+            scavenger_min_repro_age: 80,
+            plant_min_repro_age: 80,
+            herbivore_min_repro_energy: 0.8,
+            carnivore_min_repro_energy: 0.8,
+            omnivore_min_repro_energy: 0.8,
+            scavenger_min_repro_energy: 0.8,
+            plant_min_repro_energy: 0.8,
+            plant_photosynthesis: 0.001,
+            disease_outbreak_chance: 0.0,
+            disease_transmission: 0.05,
+            disease_duration: 600,
+            disease_lethality: 0.2,
+            disease_decay: 0.0005,
+            corpse_lifetime: 600,
+            corpse_energy: 0.3,
+            walls: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_toml(path: &Path) -> io::Result<Config> {
+        let text = fs::read_to_string(path)?;
+        let config: Config = toml::from_str(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        // Catch typos in preset names before any being is created
+        for being_type in BeingType::ALL {
+            for choice in config.initial_genes.get(being_type) {
+                if let Some(name) = &choice.preset && !config.gene_presets.contains_key(name) {
+                    let msg = format!("unknown gene preset {:?} for {}", name, being_type);
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    // Genes for a being of the initial population, random unless initial_genes says otherwise
+    pub fn initial_genetics(&self, being_type: BeingType, rng: &mut impl Rng) -> Genetics {
+        let choices = self.initial_genes.get(being_type);
+        let preset = WeightedIndex::new(choices.iter().map(|c| c.weight))
+            .ok()
+            .and_then(|index| choices[index.sample(rng)].preset.as_ref())
+            .and_then(|name| self.gene_presets.get(name));
+
+        match preset {
+            Some(preset) => Genetics::from_preset(preset),
+            None => Genetics::new_random(being_type, rng),
+        }
+    }
+
+    pub fn in_world(&self, x: f64, y: f64) -> bool {
+        (0.0..=self.world_width).contains(&x) && (0.0..=self.world_height).contains(&y)
+    }
+
+    pub fn max_age(&self, being_type: BeingType) -> u32 {
+        match being_type {
+            BeingType::Herbivore => self.herbivore_max_age,
+            BeingType::Carnivore => self.carnivore_max_age,
+            BeingType::Omnivore => self.omnivore_max_age,
+            BeingType::Scavenger => self.scavenger_max_age,
+            BeingType::Plant => self.plant_max_age,
+        }
+    }
+
+    pub fn repro_chance(&self, being_type: BeingType) -> f32 {
+        match being_type {
+            BeingType::Herbivore => self.herbivore_repro_chance,
+            BeingType::Carnivore => self.carnivore_repro_chance,
+            BeingType::Omnivore => self.omnivore_repro_chance,
+            BeingType::Scavenger => self.scavenger_repro_chance,
+            BeingType::Plant => self.plant_repro_chance,
+        }
+    }
+
+    pub fn min_repro_age(&self, being_type: BeingType) -> u32 {
+        match being_type {
+            BeingType::Herbivore => self.herbivore_min_repro_age,
+            BeingType::Carnivore => self.carnivore_min_repro_age,
+            BeingType::Omnivore => self.omnivore_min_repro_age,
+            BeingType::Scavenger => self.scavenger_min_repro_age,
+            BeingType::Plant => self.plant_min_repro_age,
+        }
+    }
+
+    pub fn min_repro_energy(&self, being_type: BeingType) -> f32 {
+        match being_type {
+            BeingType::Herbivore => self.herbivore_min_repro_energy,
+            BeingType::Carnivore => self.carnivore_min_repro_energy,
+            BeingType::Omnivore => self.omnivore_min_repro_energy,
+            BeingType::Scavenger => self.scavenger_min_repro_energy,
+            BeingType::Plant => self.plant_min_repro_energy,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(name: &str, text: &str) -> io::Result<Config> {
+        let path = std::env::temp_dir().join(format!("simple-life-{}-{}.toml", name, std::process::id()));
+        fs::write(&path, text)?;
+        let config = Config::from_toml(&path);
+        fs::remove_file(&path)?;
+        config
+    }
+
+    #[test]
+    fn partial_toml_keeps_the_other_defaults() {
+        let config = load("partial", "max_beings = 50\nworld_width = 1000.0\n").unwrap();
+
+        assert_eq!(config, Config { max_beings: 50, world_width: 1000.0, ..Config::default() });
+    }
+
+    #[test]
+    fn power_law_costs_more_for_big_bodies() {
+        let linear = MetabolicModel::Linear.load(2.0, 1.0);
+        let power = MetabolicModel::PowerLaw.load(2.0, 1.0);
+
+        assert_eq!(linear, 3.0);
+        assert!((power - (2.0f32.powf(1.5) + 1.0)).abs() < 1e-6);
+        assert!(power > linear);
+        // At size 1 every model agrees
+        assert_eq!(MetabolicModel::PowerLaw.load(1.0, 1.0), MetabolicModel::Linear.load(1.0, 1.0));
+    }
+}
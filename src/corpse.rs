@@ -0,0 +1,51 @@
+use piston_window::*;
+use serde::{Deserialize, Serialize};
+
+use crate::being::Being;
+use crate::config::Config;
+use crate::entity::Entity;
+use crate::vec2::Vec2;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Corpse {
+    pub x: f64,
+    pub y: f64,
+    pub energy: f32,
+    pub ticks_left: u32,
+}
+
+impl Entity for Corpse {
+    fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn energy(&self) -> f32 {
+        self.energy
+    }
+}
+
+impl Corpse {
+    // Worth a base amount per unit of body size plus whatever energy was left
+    pub fn from_being(being: &Being, config: &Config) -> Self {
+        Corpse {
+            x: being.x,
+            y: being.y,
+            energy: config.corpse_energy * being.genetics.size + being.energy.max(0.0),
+            ticks_left: config.corpse_lifetime,
+        }
+    }
+
+    pub fn pos(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    pub fn draw(&self, transform: math::Matrix2d, g: &mut G2d) {
+        let size = 4.0;
+        rectangle(
+            [0.5, 0.5, 0.5, 1.0],  // Gray
+            [self.x, self.y, size, size],
+            transform,
+            g,
+        );
+    }
+}
@@ -0,0 +1,59 @@
+// Bookkeeping of where energy enters and leaves the world during a tick, checked
+// against the actual total at the end. Only active with the `energy-audit` feature,
+// otherwise every call compiles to nothing and the closures are never run
+
+#[cfg(feature = "energy-audit")]
+const TOLERANCE: f64 = 1e-3;  // Relative to the total, f32 sums drift a little
+
+#[cfg(feature = "energy-audit")]
+pub struct EnergyAudit {
+    start: f64,
+    entries: Vec<(&'static str, f64)>,
+}
+
+#[cfg(feature = "energy-audit")]
+impl EnergyAudit {
+    pub fn begin(total: impl FnOnce() -> f64) -> Self {
+        EnergyAudit { start: total(), entries: Vec::new() }
+    }
+
+    // Expected change of the total, positive when energy enters the world
+    pub fn record(&mut self, what: &'static str, amount: impl FnOnce() -> f64) {
+        let amount = amount();
+        match self.entries.iter_mut().find(|(name, _)| *name == what) {
+            Some((_, sum)) => *sum += amount,
+            None => self.entries.push((what, amount)),
+        }
+    }
+
+    pub fn check(self, tick: u64, total: impl FnOnce() -> f64) {
+        let actual = total();
+        let expected = self.start + self.entries.iter().map(|(_, amount)| amount).sum::<f64>();
+        let unexplained = actual - expected;
+
+        if unexplained.abs() > TOLERANCE * self.start.abs().max(1.0) {
+            eprintln!("Energy audit failed at tick {}: {:.4} -> {:.4}, expected {:.4}", tick, self.start, actual, expected);
+            for (what, amount) in &self.entries {
+                eprintln!("  {:<16} {:+.4}", what, amount);
+            }
+            panic!("{:+.4} energy unaccounted for at tick {}", unexplained, tick);
+        }
+    }
+}
+
+#[cfg(not(feature = "energy-audit"))]
+pub struct EnergyAudit;
+
+#[cfg(not(feature = "energy-audit"))]
+impl EnergyAudit {
+    #[inline(always)]
+    pub fn begin(_total: impl FnOnce() -> f64) -> Self {
+        EnergyAudit
+    }
+
+    #[inline(always)]
+    pub fn record(&mut self, _what: &'static str, _amount: impl FnOnce() -> f64) {}
+
+    #[inline(always)]
+    pub fn check(self, _tick: u64, _total: impl FnOnce() -> f64) {}
+}
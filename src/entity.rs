@@ -0,0 +1,50 @@
+// Anything that sits somewhere in the world and carries energy, lets the spatial
+// indexes and the energy bookkeeping work the same way for beings, food and corpses
+pub trait Entity {
+    fn position(&self) -> (f64, f64);
+    fn energy(&self) -> f32;
+}
+
+// So a slice of `&dyn Entity` can mix beings, food and corpses in one index
+impl<E: Entity + ?Sized> Entity for &E {
+    fn position(&self) -> (f64, f64) {
+        (**self).position()
+    }
+
+    fn energy(&self) -> f32 {
+        (**self).energy()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::being::{Being, BeingType};
+    use crate::config::Config;
+    use crate::corpse::Corpse;
+    use crate::food::{Food, FoodType};
+    use crate::spatial::{QuadTree, SpatialGrid};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn spatial_indexes_take_a_mixed_slice() {
+        let being = Being::new(100.0, 100.0, BeingType::Herbivore, &Config::default(), &mut StdRng::seed_from_u64(1));
+        let food = Food { x: 52.0, y: 50.0, energy: 0.4, food_type: FoodType::Plant };
+        let corpse = Corpse { x: 40.0, y: 40.0, energy: 0.7, ticks_left: 10 };
+        let items: [&dyn Entity; 3] = [&being, &food, &corpse];
+        let grid = SpatialGrid::from_entities(&items, 50.0, 800.0, 800.0, false);
+
+        assert_eq!(grid.nearest_within(50.0, 50.0, 100.0, |_| true), Some(1));
+        assert_eq!(grid.nearest_within(35.0, 35.0, 100.0, |_| true), Some(2));
+        assert_eq!(grid.nearest_within(90.0, 95.0, 100.0, |_| true), Some(0));
+        assert_eq!(grid.nearest_within(50.0, 50.0, 100.0, |idx| items[idx].energy() > 0.5), Some(2));
+        assert_eq!(grid.nearest_within(300.0, 300.0, 100.0, |_| true), None);
+
+        let tree = QuadTree::build(&items, None);
+        let mut near: Vec<usize> = tree.query_range((45.0, 45.0), 10.0).into_iter().map(|(idx, _)| idx).collect();
+        near.sort();
+        assert_eq!(near, vec![1, 2]);
+        assert_eq!(tree.query_range((100.0, 100.0), 1.0)[0].1.energy(), being.energy);
+    }
+}
@@ -0,0 +1,82 @@
+use piston_window::*;
+use rand::Rng;
+
+use crate::being::BeingType;
+use crate::config::Config;
+
+const NIGHT_PERCEPTION: f32 = 0.5;  // Perception left at the darkest point of the night
+const BIOME_TILE: f64 = 20.0;  // Size of the squares the temperature background is drawn with
+
+// Brightness between 0 (midnight) and 1 (noon), a cycle starts at noon.
+// A day_length of 0 means permanent daylight
+pub fn time_of_day(tick: u64, day_length: u64) -> f32 {
+    if day_length == 0 {
+        return 1.0;
+    }
+
+    let phase = (tick % day_length) as f32 / day_length as f32;
+    0.5 + 0.5 * (phase * std::f32::consts::TAU).cos()
+}
+
+// Carnivores hunt by night and keep their full perception
+pub fn perception_factor(brightness: f32, being_type: BeingType) -> f32 {
+    match being_type {
+        BeingType::Carnivore => 1.0,
+        _ => NIGHT_PERCEPTION + (1.0 - NIGHT_PERCEPTION) * brightness,
+    }
+}
+
+// Coarse grid of temperatures from -1 (cold) to 1 (warm) at the cell corners,
+// smoothly interpolated in between so regions blend into each other
+pub struct Environment {
+    cell_size: f64,
+    cols: usize,  // Corners per row
+    rows: usize,
+    temperatures: Vec<f32>,
+}
+
+impl Environment {
+    pub fn new(config: &Config, rng: &mut impl Rng) -> Self {
+        let cell_size = config.biome_cell_size.max(1.0);
+        let cols = (config.world_width / cell_size).ceil() as usize + 1;
+        let rows = (config.world_height / cell_size).ceil() as usize + 1;
+        let temperatures = (0..cols * rows).map(|_| rng.random_range(-1.0..=1.0)).collect();
+        Environment { cell_size, cols, rows, temperatures }
+    }
+
+    pub fn temperature_at(&self, x: f64, y: f64) -> f32 {
+        let gx = (x / self.cell_size).clamp(0.0, (self.cols - 1) as f64);
+        let gy = (y / self.cell_size).clamp(0.0, (self.rows - 1) as f64);
+        let (col, row) = ((gx as usize).min(self.cols - 2), (gy as usize).min(self.rows - 2));
+        let (fx, fy) = ((gx - col as f64) as f32, (gy - row as f64) as f32);
+
+        let corner = |c: usize, r: usize| self.temperatures[r * self.cols + c];
+        let top = corner(col, row) * (1.0 - fx) + corner(col + 1, row) * fx;
+        let bottom = corner(col, row + 1) * (1.0 - fx) + corner(col + 1, row + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    // Multiplier on a being's energy decay, cold regions cost more to live in
+    pub fn decay_factor(&self, x: f64, y: f64, config: &Config) -> f32 {
+        1.0 - config.temperature_effect * self.temperature_at(x, y)
+    }
+
+    // Faint blue for cold, faint red for warm, nothing when temperature has no effect
+    pub fn draw(&self, config: &Config, transform: math::Matrix2d, g: &mut G2d) {
+        if config.temperature_effect == 0.0 {
+            return;
+        }
+
+        let mut y = 0.0;
+        while y < config.world_height {
+            let mut x = 0.0;
+            while x < config.world_width {
+                let t = self.temperature_at(x + BIOME_TILE / 2.0, y + BIOME_TILE / 2.0);
+                let color = if t > 0.0 { [1.0, 0.3, 0.0, 0.12 * t] } else { [0.0, 0.4, 1.0, -0.12 * t] };
+                rectangle(color, [x, y, BIOME_TILE, BIOME_TILE], transform, g);
+                x += BIOME_TILE;
+            }
+            y += BIOME_TILE;
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::being::BeingType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeathCause {
+    Eaten,
+    Starved,
+    OldAge,
+    Culled,
+}
+
+// Something that happened during a tick, returned by Simulation::tick in the order
+// it happened. A mated child's parent_id is the partner that
+// went looking for a mate
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SimEvent {
+    Birth { id: u64, parent_id: u64 },
+    Death { id: u64, cause: DeathCause },
+    Ate { predator_id: u64, energy: f32 },
+    Extinction { being_type: BeingType },
+}
+
+// Writes every event as a JSON line tagged with its tick, opt-in with --events
+pub struct EventLog {
+    out: BufWriter<File>,
+}
+
+impl EventLog {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(EventLog { out: BufWriter::new(File::create(path)?) })
+    }
+
+    pub fn write(&mut self, tick: u64, events: &[SimEvent]) -> io::Result<()> {
+        for event in events {
+            let line = serde_json::json!({ "tick": tick, "event": event });
+            writeln!(self.out, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
@@ -1,32 +1,166 @@
 use piston_window::*;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::WINDOW_SIZE;
+use crate::config::Config;
+use crate::entity::Entity;
+use crate::vec2::Vec2;
+use crate::wall::Wall;
 
-#[derive(Clone, PartialEq)]
+const FRUIT_CHANCE: f64 = 0.15;
+const CLUSTER_ATTEMPTS: usize = 10;  // Gaussian redraws before giving up and clamping
+const PLACEMENT_ATTEMPTS: usize = 10;  // Uniform redraws to find a spot outside the walls
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FoodType {
+    #[default]
+    Plant,
+    Fruit,  // Rarer than plants but worth more
+    Carrion,  // Left behind by rotting corpses, never spawned
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Food {
     pub x: f64,
     pub y: f64,
     pub energy: f32,
+    #[serde(default)]
+    pub food_type: FoodType,
+}
+
+impl Entity for Food {
+    fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn energy(&self) -> f32 {
+        self.energy
+    }
 }
 
 impl Food {
-    pub fn new() -> Self {
-        let mut rng = rand::rng();
-        Food {
-            x: rng.random_range(0.0..WINDOW_SIZE),
-            y: rng.random_range(0.0..WINDOW_SIZE),
-            energy: rng.random_range(0.3..0.7),
+    // None when no free spot outside the walls was found
+    pub fn new(config: &Config, rng: &mut impl Rng) -> Option<Self> {
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            let x = rng.random_range(0.0..config.world_width);
+            let y = rng.random_range(0.0..config.world_height);
+            if !in_wall(&config.walls, x, y) {
+                return Some(Food::at(x, y, rng));
+            }
+        }
+        None
+    }
+
+    // Spawns around one of the resource centers with a Gaussian offset
+    pub fn new_clustered(centers: &[(f64, f64)], config: &Config, rng: &mut impl Rng) -> Option<Self> {
+        if centers.is_empty() {
+            return Food::new(config, rng);
         }
+
+        let (cx, cy) = centers[rng.random_range(0..centers.len())];
+        let (spread, walls) = (config.food_cluster_spread, &config.walls);
+        let mut pos = (cx, cy);
+        for _ in 0..CLUSTER_ATTEMPTS {
+            let (gx, gy) = gaussian_pair(rng);
+            pos = (cx + gx * spread, cy + gy * spread);
+            if config.in_world(pos.0, pos.1) && !in_wall(walls, pos.0, pos.1) {
+                break;
+            }
+        }
+
+        let x = pos.0.clamp(0.0, config.world_width);
+        let y = pos.1.clamp(0.0, config.world_height);
+        if in_wall(walls, x, y) {
+            return None;
+        }
+        Some(Food::at(x, y, rng))
+    }
+
+    // Plant or fruit at a given spot
+    pub fn at(x: f64, y: f64, rng: &mut impl Rng) -> Self {
+        let (food_type, energy) = if rng.random_bool(FRUIT_CHANCE) {
+            (FoodType::Fruit, rng.random_range(0.8..1.2))
+        } else {
+            (FoodType::Plant, rng.random_range(0.3..0.7))
+        };
+
+        Food { x, y, energy, food_type }
+    }
+
+    pub fn pos(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    pub fn carrion(x: f64, y: f64, energy: f32) -> Self {
+        Food { x, y, energy, food_type: FoodType::Carrion }
     }
     
     pub fn draw(&self, transform: math::Matrix2d, g: &mut G2d) {
 	let size = 5.0;
+	let color = match self.food_type {
+            FoodType::Plant => [0.0, 1.0, 0.0, 1.0],  // Pure green
+            FoodType::Fruit => [1.0, 0.0, 1.0, 1.0],  // Magenta
+            FoodType::Carrion => [0.55, 0.35, 0.15, 1.0],  // Brown
+	};
 	rectangle(
-            [0.0, 1.0, 0.0, 1.0],  // Pure green
+            color,
             [self.x, self.y, size, size],
             transform,
             g,
 	);
     }
 }
+
+fn in_wall(walls: &[Wall], x: f64, y: f64) -> bool {
+    walls.iter().any(|w| w.contains(x, y))
+}
+
+// Two independent standard normal samples (Box-Muller)
+fn gaussian_pair(rng: &mut impl Rng) -> (f64, f64) {
+    let u1: f64 = rng.random_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.random_range(0.0..1.0);
+    let r = (-2.0 * u1.ln()).sqrt();
+    let theta = std::f64::consts::TAU * u2;
+    (r * theta.cos(), r * theta.sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn clustered_food_stays_near_a_center() {
+        let config = Config::default();
+        let centers = [(200.0, 200.0), (600.0, 400.0)];
+        let mut rng = StdRng::seed_from_u64(1);
+        let limit = config.food_cluster_spread * 3.0;
+
+        let spawns = 1000;
+        let near = (0..spawns)
+            .filter_map(|_| Food::new_clustered(&centers, &config, &mut rng))
+            .filter(|f| centers.iter().any(|&(x, y)| f.pos().distance_to(Vec2::new(x, y)) <= limit))
+            .count();
+
+        assert!(near >= spawns * 97 / 100, "{} of {} within 3 spreads", near, spawns);
+    }
+
+    #[test]
+    fn food_stays_inside_a_tall_world() {
+        let config = Config { world_width: 400.0, world_height: 1200.0, ..Config::default() };
+        let mut rng = StdRng::seed_from_u64(2);
+        let centers = [(10.0, 1190.0), (390.0, 5.0)];
+        let mut lowest: f64 = 0.0;
+
+        for _ in 0..1000 {
+            for food in [Food::new(&config, &mut rng), Food::new_clustered(&centers, &config, &mut rng)].into_iter().flatten() {
+                assert!(config.in_world(food.x, food.y), "food at ({}, {})", food.x, food.y);
+                lowest = lowest.max(food.y);
+            }
+        }
+        // The whole height is used, not just the default 600
+        assert!(lowest > 1000.0);
+    }
+}
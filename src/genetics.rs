@@ -1,38 +1,265 @@
 use rand::Rng;
-use super::BeingType;
+use serde::{Deserialize, Serialize};
+use crate::being::BeingType;
+use crate::config::MutationModel;
 
-#[derive(Clone, PartialEq)]
+// Bounds genes are clamped to when mutating
+pub const SPEED_RANGE: (f32, f32) = (0.5, 3.0);
+pub const SIZE_RANGE: (f32, f32) = (0.5, 2.0);
+pub const REPRODUCTION_RANGE: (f32, f32) = (0.1, 2.0);
+pub const PERCEPTION_RANGE: (f32, f32) = (2.0, 30.0);
+pub const METABOLISM_RANGE: (f32, f32) = (0.7, 1.3);
+pub const MUTATION_STRENGTH_RANGE: (f32, f32) = (0.2, 3.0);
+pub const DIET_PREFERENCE_RANGE: (f32, f32) = (0.0, 1.0);
+const DEFAULT_DIET_PREFERENCE: f32 = 0.7;  // The fixed hunting odds omnivores had before the gene
+const MUTATION_JITTER: f32 = 0.1;  // Max relative change of a gene at rate and strength 1
+
+// Explicit gene values for a named starting genome, see Config::gene_presets
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GenePreset {
+    pub speed: f32,
+    pub size: f32,
+    pub reproduction_rate: f32,
+    pub perception: f32,
+    pub max_energy: f32,
+    pub metabolism: f32,
+    pub mutation_strength: f32,
+    #[serde(default = "default_diet_preference")]
+    pub diet_preference: f32,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Genetics {
     pub speed: f32,
     pub size: f32,
     pub reproduction_rate: f32,
     pub perception: f32,
+    pub max_energy: f32,
+    pub metabolism: f32,  // Scales both energy extracted from food and baseline decay
+    pub mutation_strength: f32,  // Scales the jitter this genome's offspring get
+    #[serde(default = "default_diet_preference")]
+    pub diet_preference: f32,  // Odds an omnivore hunts rather than forages, 0 never hunts and 1 always does
+}
+
+fn default_diet_preference() -> f32 {
+    DEFAULT_DIET_PREFERENCE
 }
 
 impl Genetics {
-   pub fn new_random(being_type: BeingType) -> Self {
-        let mut rng = rand::rng();
+    // Genes as a color: red is speed, green perception and blue size, each scaled to its range
+    pub fn color(&self) -> [f32; 4] {
+        let scaled = |gene: f32, (min, max): (f32, f32)| ((gene - min) / (max - min)).clamp(0.0, 1.0);
+        [
+            scaled(self.speed, SPEED_RANGE),
+            scaled(self.perception, PERCEPTION_RANGE),
+            scaled(self.size, SIZE_RANGE),
+            1.0,
+        ]
+    }
+
+   pub fn new_random(being_type: BeingType, rng: &mut impl Rng) -> Self {
         let (speed_range, perception_range) = match being_type {
             BeingType::Carnivore => (2.0..4.0, 30.0..50.0), 
             BeingType::Omnivore => (0.8..2.5, 12.0..35.0),
             BeingType::Herbivore => (0.5..2.0, 6.0..25.0),
+            BeingType::Scavenger => (1.0..2.5, 15.0..40.0),
+            BeingType::Plant => (0.5..0.6, 2.0..3.0),  // Never moves, the genes only drift
         };
         
+        let size = rng.random_range(0.8..1.2);
+
         Genetics {
             speed: rng.random_range(speed_range),
-            size: rng.random_range(0.8..1.2),
+            size,
             reproduction_rate: rng.random_range(0.5..1.5),
             perception: rng.random_range(perception_range),
+            max_energy: (size * rng.random_range(1.1..1.6)).clamp(1.0, 2.0),  // Bigger bodies store more
+            metabolism: rng.random_range(METABOLISM_RANGE.0..METABOLISM_RANGE.1),
+            mutation_strength: rng.random_range(0.8..1.2),
+            // Only omnivores have a choice, the others keep a neutral value
+            diet_preference: if being_type == BeingType::Omnivore { rng.random_range(0.5..0.9) } else { DEFAULT_DIET_PREFERENCE },
         }
     }
 
-   pub fn mutate(&self) -> Self {
-        let mut rng = rand::rng();
+    // Out of range values are clamped like mutated genes are
+    pub fn from_preset(preset: &GenePreset) -> Self {
+        let clamp = |gene: f32, (min, max): (f32, f32)| gene.clamp(min, max);
+
         Genetics {
-            speed: (self.speed * rng.random_range(0.9..1.1)).clamp(0.5, 3.0),
-            size: (self.size * rng.random_range(0.9..1.1)).clamp(0.5, 2.0),
-            reproduction_rate: (self.reproduction_rate * rng.random_range(0.9..1.1)).clamp(0.1, 2.0),
-            perception: (self.perception * rng.random_range(0.9..1.1)).clamp(2.0, 30.0),
+            speed: clamp(preset.speed, SPEED_RANGE),
+            size: clamp(preset.size, SIZE_RANGE),
+            reproduction_rate: clamp(preset.reproduction_rate, REPRODUCTION_RANGE),
+            perception: clamp(preset.perception, PERCEPTION_RANGE),
+            max_energy: clamp(preset.max_energy, (1.0, 2.0)),
+            metabolism: clamp(preset.metabolism, METABOLISM_RANGE),
+            mutation_strength: clamp(preset.mutation_strength, MUTATION_STRENGTH_RANGE),
+            diet_preference: clamp(preset.diet_preference, DIET_PREFERENCE_RANGE),
+        }
+    }
+
+   // `rate` is the global mutation_rate from the config, 0 copies genes unchanged.
+   // Linked mutation scales max_energy by the same factor as size, so a bigger body
+   // also stores more
+   pub fn mutate(&self, rate: f32, model: MutationModel, rng: &mut impl Rng) -> Self {
+        let width = MUTATION_JITTER * rate * self.mutation_strength;
+        let mut scale = || 1.0 + width * rng.random_range(-1.0..=1.0);
+
+        let speed = self.speed * scale();
+        let size_scale = scale();
+        let reproduction_rate = self.reproduction_rate * scale();
+        let perception = self.perception * scale();
+        let max_energy = match model {
+            MutationModel::Independent => self.max_energy * scale(),
+            MutationModel::Linked => self.max_energy * size_scale,
+        };
+
+        let clamp = |gene: f32, (min, max): (f32, f32)| gene.clamp(min, max);
+        Genetics {
+            speed: clamp(speed, SPEED_RANGE),
+            size: clamp(self.size * size_scale, SIZE_RANGE),
+            reproduction_rate: clamp(reproduction_rate, REPRODUCTION_RANGE),
+            perception: clamp(perception, PERCEPTION_RANGE),
+            max_energy: clamp(max_energy, (1.0, 2.0)),
+            metabolism: clamp(self.metabolism * scale(), METABOLISM_RANGE),
+            mutation_strength: clamp(self.mutation_strength * scale(), MUTATION_STRENGTH_RANGE),
+            diet_preference: clamp(self.diet_preference * scale(), DIET_PREFERENCE_RANGE),
+        }
+    }
+
+    // Each gene is inherited from one parent or the other with equal odds
+    pub fn crossover(&self, other: &Genetics, rng: &mut impl Rng) -> Self {
+        Genetics {
+            speed: if rng.random_bool(0.5) { self.speed } else { other.speed },
+            size: if rng.random_bool(0.5) { self.size } else { other.size },
+            reproduction_rate: if rng.random_bool(0.5) { self.reproduction_rate } else { other.reproduction_rate },
+            perception: if rng.random_bool(0.5) { self.perception } else { other.perception },
+            max_energy: if rng.random_bool(0.5) { self.max_energy } else { other.max_energy },
+            metabolism: if rng.random_bool(0.5) { self.metabolism } else { other.metabolism },
+            mutation_strength: if rng.random_bool(0.5) { self.mutation_strength } else { other.mutation_strength },
+            diet_preference: if rng.random_bool(0.5) { self.diet_preference } else { other.diet_preference },
+        }
+    }
+
+    // Round numbers for tests that need a being to behave predictably
+    #[cfg(test)]
+    pub fn plain() -> Self {
+        Genetics {
+            speed: 1.0,
+            size: 1.0,
+            reproduction_rate: 1.0,
+            perception: 20.0,
+            max_energy: 2.0,
+            metabolism: 1.0,
+            mutation_strength: 1.0,
+            diet_preference: 0.7,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn max_energy_stays_within_bounds() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for being_type in BeingType::ALL {
+            for _ in 0..100 {
+                let mut genetics = Genetics::new_random(being_type, &mut rng);
+                assert!((1.0..=2.0).contains(&genetics.max_energy), "new {}", genetics.max_energy);
+                // Big mutations pile up against both ends of the range
+                for _ in 0..20 {
+                    genetics = genetics.mutate(10.0, MutationModel::Independent, &mut rng);
+                    assert!((1.0..=2.0).contains(&genetics.max_energy), "mutated {}", genetics.max_energy);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn crossover_takes_each_gene_from_a_parent() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let a = Genetics::new_random(BeingType::Omnivore, &mut rng);
+        let b = Genetics::new_random(BeingType::Omnivore, &mut rng);
+        let genes = |g: &Genetics| [g.speed, g.size, g.reproduction_rate, g.perception, g.max_energy, g.metabolism, g.mutation_strength, g.diet_preference];
+
+        for _ in 0..50 {
+            let child = a.crossover(&b, &mut rng);
+            for ((gene, from_a), from_b) in genes(&child).into_iter().zip(genes(&a)).zip(genes(&b)) {
+                assert!(gene == from_a || gene == from_b);
+            }
+        }
+    }
+
+    #[test]
+    fn mutation_rate_and_strength_set_the_spread() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let parent = Genetics::new_random(BeingType::Herbivore, &mut rng);
+        let mut bold = parent.clone();
+        bold.mutation_strength = MUTATION_STRENGTH_RANGE.1;
+        let mut timid = parent.clone();
+        timid.mutation_strength = MUTATION_STRENGTH_RANGE.0;
+
+        assert!(parent.mutate(0.0, MutationModel::Independent, &mut rng) == parent);
+
+        // Mean absolute change of the speed gene over many offspring
+        let mut spread = |genes: &Genetics| {
+            (0..500).map(|_| (genes.mutate(1.0, MutationModel::Independent, &mut rng).speed - genes.speed).abs()).sum::<f32>() / 500.0
+        };
+        assert!(spread(&bold) > spread(&timid) * 2.0);
+    }
+
+    #[test]
+    fn max_speed_is_fully_red() {
+        let mut genetics = Genetics::new_random(BeingType::Herbivore, &mut StdRng::seed_from_u64(5));
+        genetics.speed = SPEED_RANGE.1;
+        genetics.perception = PERCEPTION_RANGE.0;
+        genetics.size = SIZE_RANGE.0;
+
+        assert_eq!(genetics.color(), [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn from_preset_copies_the_values() {
+        let preset = GenePreset {
+            speed: 1.5,
+            size: 1.1,
+            reproduction_rate: 0.9,
+            perception: 12.0,
+            max_energy: 1.4,
+            metabolism: 1.1,
+            mutation_strength: 0.5,
+            diet_preference: 0.2,
+        };
+        let genetics = Genetics::from_preset(&preset);
+        assert!(genetics == Genetics {
+            speed: 1.5,
+            size: 1.1,
+            reproduction_rate: 0.9,
+            perception: 12.0,
+            max_energy: 1.4,
+            metabolism: 1.1,
+            mutation_strength: 0.5,
+            diet_preference: 0.2,
+        });
+
+        let extreme = Genetics::from_preset(&GenePreset { speed: 99.0, max_energy: 0.1, ..preset });
+        assert_eq!((extreme.speed, extreme.max_energy), (SPEED_RANGE.1, 1.0));
+    }
+
+    #[test]
+    fn linked_genes_keep_their_ratio() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let mut parent = Genetics::new_random(BeingType::Herbivore, &mut rng);
+        // Mid-range values, so no clamp interferes with a small mutation
+        parent.size = 1.2;
+        parent.max_energy = 1.5;
+
+        for _ in 0..100 {
+            let child = parent.mutate(1.0, MutationModel::Linked, &mut rng);
+            assert!((child.max_energy / child.size - 1.25).abs() < 1e-5, "size {} max_energy {}", child.size, child.max_energy);
         }
     }
 }
@@ -0,0 +1,25 @@
+pub mod audio;
+pub mod being;
+pub mod benchmark;
+pub mod capture;
+pub mod cli;
+pub mod config;
+pub mod corpse;
+pub mod energy_audit;
+pub mod entity;
+pub mod environment;
+pub mod events;
+pub mod food;
+pub mod genetics;
+pub mod logging;
+pub mod pheromone;
+pub mod replay;
+pub mod rng;
+pub mod simulation;
+pub mod simulation_stats;
+pub mod spatial;
+pub mod stats_server;
+pub mod sweep;
+pub mod timestep;
+pub mod vec2;
+pub mod wall;
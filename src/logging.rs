@@ -0,0 +1,25 @@
+use log::{LevelFilter, Log, Metadata, Record};
+
+// Plain stderr logger for --verbose. Without it the log level stays off and every
+// log call is a single comparison
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        eprintln!("{:<5} {}", record.level(), record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+pub fn init(level: LevelFilter) {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
@@ -1,181 +1,265 @@
 use piston_window::*;
-use rand::Rng;
-use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process;
 use std::time::Instant;
-use std::sync::{Arc, Mutex};
-
-mod being;
-mod food;
-mod genetics;
-mod simulation_stats;
-
-use being::{Being, BeingType};
-use food::Food;
-use simulation_stats::SimulationStats;
-
-const WINDOW_SIZE: f64 = 800.0;
-const BASE_BEING_SIZE: f64 = 10.0;
-const MAX_BEINGS: usize = 220;
-const MAX_FOOD: usize = 790;
-const FOOD_SPAWN_RATE: f64 = 0.99;
-const ENERGY_DECAY: f32 = 0.0000015;
-const STATS_AREA_HEIGHT: f64 = 50.0; // New constant for stats area height
-const TOTAL_WINDOW_HEIGHT: f64 = WINDOW_SIZE + STATS_AREA_HEIGHT; // New total window height
+
+use simple_life::{
+    audio, being, benchmark, capture, cli, config, environment, events, logging, replay, simulation, simulation_stats,
+    stats_server, sweep, timestep,
+};
+
+use audio::{AplaySink, EventSounds};
+use being::{BeingType, ColorMode};
+use capture::FrameCapture;
+use cli::CliArgs;
+use config::Config;
+use events::{EventLog, SimEvent};
+use replay::Recorder;
+use simulation::Simulation;
+use stats_server::StatsServer;
+use timestep::FixedTimestep;
+use simulation_stats::{count_by_type, SimulationStats, ENERGY_SMOOTHING_WINDOW};
+
+const TICKS_PER_SECOND: u64 = 120; // Fixed simulation rate in windowed mode
+const MAX_CATCH_UP_TICKS: u32 = 8; // Slower frames than this drop the backlog instead of catching up
+const DEFAULT_HEADLESS_TICKS: u64 = 1000;
+const BENCHMARK_SEED: u64 = 0; // Benchmarks are comparable across runs unless --seed says otherwise
+const STATS_AREA_HEIGHT: f64 = 80.0; // Text line on top, population graph below it
+const GRAPH_TOP: f64 = 40.0; // Below the stats text baseline
+const GRAPH_TICKS: usize = 300; // How much history the graph shows
+const MAX_TICKS_PER_FRAME: u32 = 32; // Fastest fast-forward, simulation ticks per update
+const SCREENSHOT_DIR: &str = "screenshots";
 
 fn main() {
-    let mut window: PistonWindow = WindowSettings::new(
-        "Parallel Virtual Ecosystem",
-        [WINDOW_SIZE as u32, TOTAL_WINDOW_HEIGHT as u32], // Updated window height
-    )
-    .exit_on_esc(true)
-    .build()
-    .unwrap();
+    let args = CliArgs::parse();
+    if args.verbose {
+        logging::init(log::LevelFilter::Trace);
+    }
 
-    // Initialize beings with different types
-    let mut beings = vec![
-        Being::new(WINDOW_SIZE / 3.0, WINDOW_SIZE / 3.0, BeingType::Herbivore),
-        Being::new(WINDOW_SIZE / 4.0, WINDOW_SIZE / 4.0, BeingType::Herbivore),
-        Being::new(WINDOW_SIZE * 2.0 / 3.0, WINDOW_SIZE / 3.0, BeingType::Carnivore),
-        Being::new(WINDOW_SIZE * 3.0 / 4.0, WINDOW_SIZE / 4.0, BeingType::Carnivore),
-        Being::new(WINDOW_SIZE / 2.0, WINDOW_SIZE * 2.0 / 3.0, BeingType::Omnivore),
-    ];
-    
-    let foods = Arc::new(Mutex::new(Vec::<Food>::new()));
-    let mut rng = rand::rng();
-    
-    // Load font
-    let mut glyphs = {
-        let font_path = std::path::Path::new("assets/FiraSans-Regular.ttf");
-        if font_path.exists() {
-            window.load_font(font_path).ok()
-        } else {
-            eprintln!("Could not load font file at {:?}", font_path);
-            None
-        }
+    // Replays only draw recorded frames, no simulation is set up
+    if let Some(path) = &args.replay {
+        run_replay(path, args.font.as_deref());
+        return;
+    }
+
+    let seed = match args.seed {
+        Some(seed) => seed,
+        None if args.benchmark => BENCHMARK_SEED,
+        None => rand::random(),
     };
-	
-    
-    
-    let mut stats = SimulationStats {
-        energy_history: Vec::with_capacity(1000),
-        population_history: Vec::with_capacity(1000),
-        ..Default::default()
+    eprintln!("Running with seed {} (pass --seed {} to reproduce)", seed, seed);
+
+    // Several headless runs side by side, one per config file
+    if !args.sweep.is_empty() {
+        run_sweep(&args.sweep, args.ticks.unwrap_or(DEFAULT_HEADLESS_TICKS), seed);
+        return;
+    }
+
+    let config = match &args.config {
+        Some(path) => Config::from_toml(path).unwrap_or_else(|err| {
+            eprintln!("Could not load config {:?}: {}", path, err);
+            process::exit(1);
+        }),
+        None => Config::default(),
     };
 
+    if args.benchmark {
+        let result = benchmark::run_benchmark(config, seed, args.ticks.unwrap_or(DEFAULT_HEADLESS_TICKS));
+        println!("{}", serde_json::json!(result));
+        return;
+    }
+
+    let mut sim = match &args.load {
+        Some(path) => Simulation::load(path, config).unwrap_or_else(|err| {
+            eprintln!("Could not load snapshot {:?}: {}", path, err);
+            process::exit(1);
+        }),
+        None => Simulation::new(config, seed),
+    };
+
+    let mut recorder = args.record.as_ref().map(|path| {
+        Recorder::create(path, sim.config.world_width, sim.config.world_height).unwrap_or_else(|err| {
+            eprintln!("Could not create recording {:?}: {}", path, err);
+            process::exit(1);
+        })
+    });
+
+    let mut event_log = args.events.as_ref().map(|path| {
+        EventLog::create(path).unwrap_or_else(|err| {
+            eprintln!("Could not create event log {:?}: {}", path, err);
+            process::exit(1);
+        })
+    });
+
+    let stats_server = args.stats_port.map(|port| {
+        StatsServer::bind(port).unwrap_or_else(|err| {
+            eprintln!("Could not listen on port {}: {}", port, err);
+            process::exit(1);
+        })
+    });
+    let stats_server = stats_server.as_ref();
+
+    // No window or GPU needed, just run and report
+    if args.headless {
+        run_headless(&mut sim, &mut recorder, &mut event_log, stats_server, args.ticks.unwrap_or(DEFAULT_HEADLESS_TICKS));
+        finish_recording(recorder, event_log);
+        if let Some(path) = &args.genomes {
+            export_genomes(&sim, path);
+        }
+        return;
+    }
+
+    let (world_width, world_height) = (sim.config.world_width, sim.config.world_height);
+    let mut window = open_window(world_width, world_height);
+    let mut glyphs = load_glyphs(&mut window, args.font.as_deref());
+
     let mut last_time = Instant::now();
     let mut fps = 0.0;
+    let mut show_energy_bars = false;
+    let mut color_mode = ColorMode::Type;
+    let mut paused = false;
+    let mut show_graph = true;
+    let mut show_perception = false;
+    let mut cursor = [0.0, 0.0];
+    let mut spawn_type = args.spawn_type.unwrap_or(BeingType::Herbivore);
+    let mut ticks_per_frame: u32 = 1;
+    let mut timestep = FixedTimestep::new(TICKS_PER_SECOND, MAX_CATCH_UP_TICKS);
+    let mut last_frame = Instant::now();
+    let mut window_size = initial_window_size(&window);
+    let mut capture = FrameCapture::new(SCREENSHOT_DIR);
+    let mut capture_next_frame = false;
+    let mut frames_rendered: u64 = 0;
+    let mut audio = args.audio.then(AplaySink::spawn).and_then(|sink| match sink {
+        Ok(sink) => Some(EventSounds::new(sink)),
+        Err(err) => {
+            eprintln!("Audio disabled: {}", err);
+            None
+        },
+    });
+    // Counted from where the run starts, loaded snapshots included
+    let stop_tick = args.max_ticks.map(|n| sim.current_tick() + n);
+    let finished = |sim: &Simulation| stop_tick.is_some_and(|stop| sim.current_tick() >= stop);
     
     while let Some(e) = window.next() {
-	// Calculate FPS
-        let now = Instant::now();
-        let delta_time = now.duration_since(last_time).as_secs_f64();
-        last_time = now;
-        fps = 0.9 * fps + 0.1 * (1.0 / delta_time);
-	
-        // Track population history
-        stats.population_history.push(beings.len());
-        if beings.len() > stats.max_population {
-            stats.max_population = beings.len();
+        if finished(&sim) {
+            break;
         }
-        
-        // Spawn food
-        if foods.lock().unwrap().len() < MAX_FOOD && rng.random_range(0.0..1.0) < FOOD_SPAWN_RATE {
-            foods.lock().unwrap().push(Food::new());
+
+        if let Some(args) = e.resize_args() {
+            window_size = args.window_size;
         }
-        
-        // Parallel being updates
-        let beings_copy = beings.clone();
-        let foods_copy = foods.lock().unwrap().clone();
-        let foods_ref = Arc::clone(&foods);
-        
-        let updates: Vec<(Being, Vec<usize>, Option<Being>)> = beings.par_iter_mut()
-            .map(|being| {
-                let (eaten_food_indices, new_being) = being.update(&beings_copy, &foods_copy);
-                (being.clone(), eaten_food_indices, new_being)
-            })
-            .collect();
-        
-        // Process updates and track statistics
+
+        if let Some(pos) = e.mouse_cursor_args() {
+            cursor = pos;
+        }
+
+        // Left click drops a being of the selected type, right click drops food
+        if let Some(Button::Mouse(button)) = e.press_args()
+            && let Some((x, y)) = screen_to_sim(cursor, window_size, world_width, world_height)
         {
-            let mut foods = foods_ref.lock().unwrap();
-            for (_, eaten_food_indices, _) in updates.iter() {
-                stats.food_eaten += eaten_food_indices.len();
-                // When processing eaten food:
-		for &idx in eaten_food_indices.iter().rev() {
-		    if idx < foods.len() {
-			foods.remove(idx);
-		    }
-		}
+            match button {
+                MouseButton::Left => sim.spawn_being(x, y, spawn_type),
+                MouseButton::Right => sim.spawn_food(x, y),
+                _ => {}
             }
         }
-        
-        // Track energy history
-        if !beings.is_empty() {
-            let avg_energy = beings.iter().map(|b| b.energy).sum::<f32>() / beings.len() as f32;
-            stats.energy_history.push(avg_energy);
+
+        if let Some(Button::Keyboard(key)) = e.press_args() {
+            match key {
+                Key::D1 => spawn_type = BeingType::Herbivore,
+                Key::D2 => spawn_type = BeingType::Carnivore,
+                Key::D3 => spawn_type = BeingType::Omnivore,
+                Key::D4 => spawn_type = BeingType::Scavenger,
+                Key::D5 => spawn_type = BeingType::Plant,
+                Key::S => match sim.save(Path::new("snapshot.json")) {
+                    Ok(()) => eprintln!("Saved snapshot.json"),
+                    Err(err) => eprintln!("Could not save snapshot: {}", err),
+                },
+                Key::E => export_stats(&sim),
+                Key::L => match sim.export_lineage_dot(Path::new("lineage.dot")) {
+                    Ok(()) => eprintln!("Exported lineage.dot"),
+                    Err(err) => eprintln!("Could not export lineage: {}", err),
+                },
+                Key::R => sim.stats.reset(),
+                Key::J => export_genomes(&sim, Path::new("genomes.json")),
+                Key::B => show_energy_bars = !show_energy_bars,
+                Key::C => color_mode = color_mode.toggled(),
+                Key::G => show_graph = !show_graph,
+                Key::P => show_perception = !show_perception,
+                Key::F12 => capture_next_frame = true,
+                // Doubles or halves the speed, + shares its key with = on most layouts
+                Key::Plus | Key::Equals | Key::NumPadPlus => ticks_per_frame = (ticks_per_frame * 2).min(MAX_TICKS_PER_FRAME),
+                Key::Minus | Key::NumPadMinus => ticks_per_frame = (ticks_per_frame / 2).max(1),
+                Key::Space => paused = !paused,
+                Key::N if paused => {
+                    let events = step(&mut sim, &mut recorder, &mut event_log, stats_server);
+                    play_sounds(&mut audio, &events);
+                },
+                _ => {}
+            }
         }
-        
-        // Update beings and track births/deaths
-        beings = updates.into_iter()
-            .flat_map(|(being, _, new_being)| {
-                if new_being.is_some() {
-                    stats.total_births += 1;
-                }
-                let mut beings = Vec::new();
-                beings.push(being);
-                if let Some(b) = new_being {
-                    beings.push(b);
-                }
-                beings
-            })
-            .filter(|b| {
-                if b.energy <= 0.0 || b.age > b.max_age {
-                    stats.total_deaths += 1;
-                    false
-                } else {
-                    true
+
+        // Ticks follow real time, however often frames are rendered
+        if e.render_args().is_some() {
+            let now = Instant::now();
+            let elapsed = now.duration_since(last_frame).as_secs_f64();
+            last_frame = now;
+
+            if paused {
+                timestep.reset();
+            } else {
+                for _ in 0..timestep.advance(elapsed) * ticks_per_frame {
+                    if finished(&sim) {
+                        break;
+                    }
+                    let events = step(&mut sim, &mut recorder, &mut event_log, stats_server);
+                    play_sounds(&mut audio, &events);
                 }
-            })
-            .collect();
-        
-        // Enforce population limit
-        if beings.len() > MAX_BEINGS {
-            beings.truncate(MAX_BEINGS);
-        }
-        
-        // Keep history buffers manageable
-        if stats.population_history.len() > 1000 {
-            stats.population_history.remove(0);
+            }
         }
-        if stats.energy_history.len() > 1000 {
-            stats.energy_history.remove(0);
+
+	// Calculate FPS over rendered frames only
+        if e.render_args().is_some() {
+            let now = Instant::now();
+            let delta_time = now.duration_since(last_time).as_secs_f64();
+            last_time = now;
+            fps = 0.9 * fps + 0.1 * (1.0 / delta_time);
         }
         
         // Draw everything
+        let brightness = environment::time_of_day(sim.current_tick(), sim.config.day_length);
         window.draw_2d(&e, |c, g, device| {
-            // Clear entire window
-            clear([0.1, 0.1, 0.1, 1.0], g);
+            // Clear entire window, darker at night
+            let shade = 0.03 + 0.07 * brightness;
+            clear([shade, shade, shade + 0.02 * (1.0 - brightness), 1.0], g);
             
             // Draw stats area background
             rectangle(
                 [0.2, 0.2, 0.2, 1.0], // Darker background for stats area
-                [0.0, 0.0, WINDOW_SIZE, STATS_AREA_HEIGHT],
+                [0.0, 0.0, window_size[0], STATS_AREA_HEIGHT],
                 c.transform,
                 g,
             );
             
             // Draw stats text
 	    if let Some(ref mut glyphs) = glyphs {
+		let counts = count_by_type(&sim.beings);
 		let stats_text = format!(
-		    "Pop: {}/{} | H:{} C:{} O:{} | Food: {} | Threads: {} | FPS: {:.1} ",
-		    beings.len(),
-		    MAX_BEINGS,
-		    beings.iter().filter(|b| b.being_type == BeingType::Herbivore).count(),
-		    beings.iter().filter(|b| b.being_type == BeingType::Carnivore).count(),
-		    beings.iter().filter(|b| b.being_type == BeingType::Omnivore).count(),
-		    foods.lock().unwrap().len(),
+		    "Pop: {}/{} | H:{} C:{} O:{} S:{} P:{} | Inf: {} | Food: {} | Div: {:.3} | Threads: {} | x{} | FPS: {:.1} {}",
+		    sim.beings.len(),
+		    sim.config.max_beings,
+		    counts.herbivores,
+		    counts.carnivores,
+		    counts.omnivores,
+		    counts.scavengers,
+		    counts.plants,
+		    sim.infected_count(),
+		    sim.foods.len(),
+		    sim.stats.diversity_history.back().copied().unwrap_or(0.0),
 		    rayon::current_num_threads(),
-		    fps
+		    ticks_per_frame,
+		    fps,
+		    if paused { "| PAUSED" } else { "" }
 		);
 		
 		// White text on dark background
@@ -192,19 +276,359 @@ fn main() {
 		// Important: Flush the glyphs
 		glyphs.factory.encoder.flush(device);
 	    }
-            // Create transform for simulation area (offset by STATS_AREA_HEIGHT)
-            let sim_transform = c.transform.trans(0.0, STATS_AREA_HEIGHT);
+
+            if show_graph {
+                draw_population_graph(&sim.stats, window_size[0], c.transform, g);
+            }
+
+            // The world is scaled to fill the window below the stats bar
+            let world_transform = world_to_screen_transform(window_size[0], window_size[1], world_width, world_height);
+            let sim_transform = math::multiply(c.transform, world_transform);
             
+            sim.environment.draw(&sim.config, sim_transform, g);
+            if sim.config.pheromone_deposit > 0.0 {
+                sim.pheromones.draw(sim_transform, g);
+            }
+
+            // Draw walls in simulation area
+            for wall in &sim.config.walls {
+                wall.draw(sim_transform, g);
+            }
+
             // Draw foods in simulation area
-            let foods = foods.lock().unwrap();
-            for food in foods.iter() {
+            for food in &sim.foods {
                 food.draw(sim_transform, g);
             }
+
+            // Draw corpses in simulation area
+            for corpse in &sim.corpses {
+                corpse.draw(sim_transform, g);
+            }
             
+            // Sensing ranges under the beings, only when asked for since it's a lot of outlines
+            if show_perception {
+                for being in &sim.beings {
+                    being.draw_perception(sim.current_tick(), &sim.config, sim_transform, g);
+                }
+            }
+
             // Draw beings in simulation area
-            for being in &beings {
-                being.draw(sim_transform, g);
+            for being in &sim.beings {
+                being.draw(sim_transform, show_energy_bars, color_mode, g);
+            }
+
+            // Spawn hint in the bottom corner
+            if let Some(ref mut glyphs) = glyphs {
+                let hint = format!("Click: spawn {:?} (1-5 to change) | Right click: food", spawn_type);
+                text::Text::new_color([1.0, 1.0, 1.0, 0.6], 14)
+                    .draw(&hint, glyphs, &c.draw_state, c.transform.trans(10.0, window_size[1] - 10.0), g)
+                    .unwrap();
+                glyphs.factory.encoder.flush(device);
             }
         });
+
+        // Grab the finished frame before it's swapped out
+        if e.render_args().is_some() {
+            frames_rendered += 1;
+            if capture_next_frame || args.capture_every.is_some_and(|n| frames_rendered.is_multiple_of(n)) {
+                capture_next_frame = false;
+                match capture.save(&mut window) {
+                    Ok(path) => eprintln!("Saved {}", path.display()),
+                    Err(err) => eprintln!("Could not save frame: {}", err),
+                }
+            }
+        }
+    }
+
+    // Window closed or --max-ticks reached, keep the collected history
+    export_stats(&sim);
+    if let Some(path) = &args.genomes {
+        export_genomes(&sim, path);
+    }
+    finish_recording(recorder, event_log);
+}
+
+// Uniform scale that fits the world into the window below the stats bar
+fn world_scale(win_w: f64, win_h: f64, world_width: f64, world_height: f64) -> f64 {
+    (win_w / world_width).min((win_h - STATS_AREA_HEIGHT).max(0.0) / world_height)
+}
+
+// World coordinates to window pixels, the stats bar keeps its pixel height
+fn world_to_screen_transform(win_w: f64, win_h: f64, world_width: f64, world_height: f64) -> math::Matrix2d {
+    let scale = world_scale(win_w, win_h, world_width, world_height);
+    math::identity().trans(0.0, STATS_AREA_HEIGHT).scale(scale, scale)
+}
+
+// Window coordinates to world coordinates, None outside the drawn world
+fn screen_to_sim(pos: [f64; 2], window_size: [f64; 2], world_width: f64, world_height: f64) -> Option<(f64, f64)> {
+    let scale = world_scale(window_size[0], window_size[1], world_width, world_height);
+    if scale <= 0.0 {
+        return None;
+    }
+
+    let (x, y) = (pos[0] / scale, (pos[1] - STATS_AREA_HEIGHT) / scale);
+    (x >= 0.0 && y >= 0.0 && x <= world_width && y <= world_height).then_some((x, y))
+}
+
+fn initial_window_size(window: &PistonWindow) -> [f64; 2] {
+    let size = window.size();
+    [size.width, size.height]
+}
+
+// The world below a fixed-height stats area
+fn open_window(world_width: f64, world_height: f64) -> PistonWindow {
+    let mut window: PistonWindow = WindowSettings::new(
+        "Parallel Virtual Ecosystem",
+        [world_width as u32, (world_height + STATS_AREA_HEIGHT) as u32],
+    )
+    .exit_on_esc(true)
+    .resizable(true)
+    .build()
+    .unwrap();
+
+    // Replays advance on piston's fixed-rate update events, the simulation
+    // keeps its own timestep
+    window.set_ups(TICKS_PER_SECOND);
+    window
+}
+
+// Compiled into the binary so the stats text works wherever the program is started from
+const FONT: &[u8] = include_bytes!("../assets/FiraSans-Regular.ttf");
+
+// A font given with --font wins, the built-in one is the fallback
+fn load_glyphs(window: &mut PistonWindow, font: Option<&Path>) -> Option<Glyphs> {
+    if let Some(path) = font {
+        match window.load_font(path) {
+            Ok(glyphs) => return Some(glyphs),
+            Err(err) => eprintln!("Could not load font {:?}: {}, using the built-in one", path, err),
+        }
+    }
+
+    let glyphs = Glyphs::from_bytes(FONT, window.create_texture_context(), TextureSettings::new()).ok();
+    if glyphs.is_none() {
+        eprintln!("Could not load the built-in font, stats text is disabled");
+    }
+    glyphs
+}
+
+// Advances one tick and appends it to the recording and event log, if any
+fn step(sim: &mut Simulation, recorder: &mut Option<Recorder>, event_log: &mut Option<EventLog>, stats_server: Option<&StatsServer>) -> Vec<SimEvent> {
+    let tick = sim.current_tick();
+    let events = sim.tick();
+
+    if let Some(Err(err)) = event_log.as_mut().map(|log| log.write(tick, &events)) {
+        eprintln!("Event log stopped: {}", err);
+        *event_log = None;
+    }
+
+    if let Some(server) = stats_server {
+        server.publish(&sim.snapshot_stats());
+    }
+
+    if let Some(Err(err)) = recorder.as_mut().map(|rec| rec.record(sim)) {
+        eprintln!("Recording stopped: {}", err);
+        *recorder = None;
+    }
+    events
+}
+
+fn play_sounds(audio: &mut Option<EventSounds<AplaySink>>, events: &[SimEvent]) {
+    if let Some(audio) = audio {
+        audio.handle(events, Instant::now());
+    }
+}
+
+fn finish_recording(recorder: Option<Recorder>, event_log: Option<EventLog>) {
+    if let Some(Err(err)) = recorder.map(Recorder::finish) {
+        eprintln!("Could not finish recording: {}", err);
+    }
+    if let Some(Err(err)) = event_log.map(EventLog::finish) {
+        eprintln!("Could not finish event log: {}", err);
+    }
+}
+
+fn run_replay(path: &Path, font: Option<&Path>) {
+    let recording = replay::load(path).unwrap_or_else(|err| {
+        eprintln!("Could not load replay {:?}: {}", path, err);
+        process::exit(1);
+    });
+    let frames = recording.frames;
+    if frames.is_empty() {
+        eprintln!("Replay {:?} has no frames", path);
+        return;
+    }
+
+    let mut window = open_window(recording.world_width, recording.world_height);
+    let mut glyphs = load_glyphs(&mut window, font);
+    let mut current = 0;
+    let mut paused = false;
+    let mut window_size = initial_window_size(&window);
+
+    while let Some(e) = window.next() {
+        if let Some(args) = e.resize_args() {
+            window_size = args.window_size;
+        }
+
+        if let Some(Button::Keyboard(key)) = e.press_args() {
+            match key {
+                Key::Space => paused = !paused,
+                Key::N if paused => current = (current + 1).min(frames.len() - 1),
+                _ => {}
+            }
+        }
+
+        // Stops on the last frame
+        if e.update_args().is_some() && !paused {
+            current = (current + 1).min(frames.len() - 1);
+        }
+
+        window.draw_2d(&e, |c, g, device| {
+            clear([0.1, 0.1, 0.1, 1.0], g);
+            rectangle([0.2, 0.2, 0.2, 1.0], [0.0, 0.0, window_size[0], STATS_AREA_HEIGHT], c.transform, g);
+
+            let frame = &frames[current];
+            if let Some(ref mut glyphs) = glyphs {
+                let text = format!(
+                    "Replay: frame {}/{} | Pop: {} | Food: {} {}",
+                    current + 1,
+                    frames.len(),
+                    frame.beings.len(),
+                    frame.foods.len(),
+                    if paused { "| PAUSED" } else { "" }
+                );
+                text::Text::new_color([1.0, 1.0, 1.0, 1.0], 20)
+                    .draw(&text, glyphs, &c.draw_state, c.transform.trans(10.0, 30.0), g)
+                    .unwrap();
+                glyphs.factory.encoder.flush(device);
+            }
+
+            let world_transform = world_to_screen_transform(
+                window_size[0],
+                window_size[1],
+                recording.world_width,
+                recording.world_height,
+            );
+            frame.draw(math::multiply(c.transform, world_transform), g);
+        });
+    }
+}
+
+// Line graph of recent population history, total in white plus one line per type,
+// and the smoothed average energy in yellow on its own 0..2 scale
+fn draw_population_graph(stats: &SimulationStats, area_width: f64, transform: math::Matrix2d, g: &mut G2d) {
+    let (left, width) = (10.0, area_width - 20.0);
+    let height = STATS_AREA_HEIGHT - GRAPH_TOP - 4.0;
+    let bottom = GRAPH_TOP + height;
+    let scale = stats.max_population.max(1) as f64;
+    let step = width / (GRAPH_TICKS - 1) as f64;
+
+    let series = [
+        ([1.0, 1.0, 1.0, 1.0], &stats.population_history),
+        (BeingType::Herbivore.color(), &stats.herbivore_history),
+        (BeingType::Carnivore.color(), &stats.carnivore_history),
+        (BeingType::Omnivore.color(), &stats.omnivore_history),
+        (BeingType::Scavenger.color(), &stats.scavenger_history),
+        (BeingType::Plant.color(), &stats.plant_history),
+    ];
+
+    let mut draw_series = |color: [f32; 4], values: Vec<f64>, scale: f64| {
+        let recent = &values[values.len().saturating_sub(GRAPH_TICKS)..];
+        let point = |i: usize| (left + i as f64 * step, bottom - (recent[i] / scale).min(1.0) * height);
+
+        for i in 1..recent.len() {
+            let (x0, y0) = point(i - 1);
+            let (x1, y1) = point(i);
+            line(color, 0.5, [x0, y0, x1, y1], transform, g);
+        }
+    };
+
+    for (color, history) in series {
+        draw_series(color, history.iter().map(|&n| n as f64).collect(), scale);
+    }
+
+    let energy = stats.smoothed_energy(ENERGY_SMOOTHING_WINDOW);
+    draw_series([1.0, 0.9, 0.2, 1.0], energy.into_iter().map(f64::from).collect(), 2.0);
+}
+
+fn run_headless(sim: &mut Simulation, recorder: &mut Option<Recorder>, event_log: &mut Option<EventLog>, stats_server: Option<&StatsServer>, ticks: u64) {
+    for _ in 0..ticks {
+        step(sim, recorder, event_log, stats_server);
+    }
+
+    let snapshot = sim.snapshot_stats();
+    let counts = snapshot.counts;
+    let summary = serde_json::json!({
+        "ticks": ticks,
+        "final_tick": snapshot.tick,
+        "total_births": snapshot.total_births,
+        "total_deaths": snapshot.total_deaths,
+        "max_population": sim.stats.max_population,
+        "food_eaten": sim.stats.food_eaten,
+        "herbivores": counts.herbivores,
+        "carnivores": counts.carnivores,
+        "omnivores": counts.omnivores,
+        "scavengers": counts.scavengers,
+        "plants": counts.plants,
+        "epochs": sim.stats.epochs,
+        "state_hash": format!("{:016x}", sim.state_hash()),
+    });
+    println!("{}", summary);
+}
+
+fn run_sweep(paths: &[PathBuf], ticks: u64, seed: u64) {
+    let configs = paths.iter()
+        .map(|path| Config::from_toml(path).unwrap_or_else(|err| {
+            eprintln!("Could not load config {:?}: {}", path, err);
+            process::exit(1);
+        }))
+        .collect();
+
+    let results: Vec<_> = sweep::run_sweep(configs, ticks, seed)
+        .into_iter()
+        .zip(paths)
+        .map(|(stats, path)| serde_json::json!({ "config": path, "stats": stats }))
+        .collect();
+    println!("{}", serde_json::Value::Array(results));
+}
+
+fn export_genomes(sim: &Simulation, path: &Path) {
+    match sim.export_genomes(path) {
+        Ok(()) => eprintln!("Exported {:?}", path),
+        Err(err) => eprintln!("Could not export genomes: {}", err),
+    }
+}
+
+fn export_stats(sim: &Simulation) {
+    match sim.stats.export_csv(Path::new("stats.csv")) {
+        Ok(()) => eprintln!("Exported stats.csv"),
+        Err(err) => eprintln!("Could not export stats: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicks_map_to_world_coordinates() {
+        let window = [800.0, 600.0 + STATS_AREA_HEIGHT];
+
+        assert_eq!(screen_to_sim([100.0, STATS_AREA_HEIGHT + 50.0], window, 800.0, 600.0), Some((100.0, 50.0)));
+        assert_eq!(screen_to_sim([100.0, STATS_AREA_HEIGHT - 1.0], window, 800.0, 600.0), None);
+        // A window twice the size of a world shows it at double scale
+        assert_eq!(screen_to_sim([200.0, STATS_AREA_HEIGHT + 100.0], window, 400.0, 300.0), Some((100.0, 50.0)));
+    }
+
+    #[test]
+    fn world_fills_the_window_below_the_stats_bar() {
+        let apply = |m: math::Matrix2d, (x, y): (f64, f64)| (m[0][0] * x + m[0][1] * y + m[0][2], m[1][0] * x + m[1][1] * y + m[1][2]);
+
+        // Twice as wide as the world but only just as tall, height limits the scale
+        let transform = world_to_screen_transform(1600.0, 600.0 + STATS_AREA_HEIGHT, 800.0, 600.0);
+        assert_eq!(apply(transform, (0.0, 0.0)), (0.0, STATS_AREA_HEIGHT));
+        assert_eq!(apply(transform, (800.0, 600.0)), (800.0, 600.0 + STATS_AREA_HEIGHT));
+
+        let transform = world_to_screen_transform(1600.0, 1200.0 + STATS_AREA_HEIGHT, 800.0, 600.0);
+        assert_eq!(apply(transform, (800.0, 600.0)), (1600.0, 1200.0 + STATS_AREA_HEIGHT));
     }
 }
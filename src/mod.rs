@@ -1,4 +0,0 @@
-pub mod being;
-pub mod food;
-pub mod genetics;
-pub mod simulation_stats;
@@ -0,0 +1,94 @@
+use piston_window::*;
+
+use crate::vec2::Vec2;
+
+const MAX_SHOWN: f32 = 5.0;  // Concentration drawn at full overlay strength
+
+// Coarse scalar field that herbivores mark as they walk and that fades over time,
+// so well-used paths stand out from the rest of the world
+pub struct PheromoneGrid {
+    cell_size: f64,
+    cols: usize,
+    rows: usize,
+    values: Vec<f32>,
+}
+
+impl PheromoneGrid {
+    pub fn new(width: f64, height: f64, cell_size: f64) -> Self {
+        let cols = (width / cell_size).ceil().max(1.0) as usize;
+        let rows = (height / cell_size).ceil().max(1.0) as usize;
+        PheromoneGrid { cell_size, cols, rows, values: vec![0.0; cols * rows] }
+    }
+
+    fn cell(&self, x: f64, y: f64) -> (usize, usize) {
+        let col = ((x / self.cell_size).max(0.0) as usize).min(self.cols - 1);
+        let row = ((y / self.cell_size).max(0.0) as usize).min(self.rows - 1);
+        (col, row)
+    }
+
+    fn value(&self, col: usize, row: usize) -> f32 {
+        self.values[row * self.cols + col]
+    }
+
+    pub fn deposit(&mut self, x: f64, y: f64, amount: f32) {
+        let (col, row) = self.cell(x, y);
+        self.values[row * self.cols + col] += amount;
+    }
+
+    // Every cell loses `rate` of its concentration
+    pub fn decay(&mut self, rate: f32) {
+        let keep = (1.0 - rate).clamp(0.0, 1.0);
+        for value in &mut self.values {
+            *value *= keep;
+        }
+    }
+
+    // Direction of increasing concentration from the neighbouring cells, per unit of
+    // distance. Zero on a flat field, the world edge counts as the edge cell itself
+    pub fn gradient_at(&self, x: f64, y: f64) -> Vec2 {
+        let (col, row) = self.cell(x, y);
+        let (left, right) = (col.saturating_sub(1), (col + 1).min(self.cols - 1));
+        let (up, down) = (row.saturating_sub(1), (row + 1).min(self.rows - 1));
+
+        let dx = (self.value(right, row) - self.value(left, row)) as f64 / ((right - left).max(1) as f64 * self.cell_size);
+        let dy = (self.value(col, down) - self.value(col, up)) as f64 / ((down - up).max(1) as f64 * self.cell_size);
+        Vec2::new(dx, dy)
+    }
+
+    pub fn draw(&self, transform: math::Matrix2d, g: &mut G2d) {
+        for (i, &value) in self.values.iter().enumerate() {
+            let strength = (value / MAX_SHOWN).min(1.0);
+            if strength < 0.01 {
+                continue;
+            }
+            let (col, row) = (i % self.cols, i / self.cols);
+            rectangle(
+                [0.9, 0.8, 0.3, 0.2 * strength],
+                [col as f64 * self.cell_size, row as f64 * self.cell_size, self.cell_size, self.cell_size],
+                transform,
+                g,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deposits_fade_and_attract() {
+        let mut grid = PheromoneGrid::new(100.0, 100.0, 10.0);
+        grid.deposit(55.0, 25.0, 2.0);
+
+        grid.decay(0.25);
+        assert_eq!(grid.value(5, 2), 1.5);
+
+        // East of the deposit the trail lies west, below it the trail lies up
+        let east = grid.gradient_at(65.0, 25.0);
+        let south = grid.gradient_at(55.0, 35.0);
+        assert!(east.x < 0.0 && east.y == 0.0);
+        assert!(south.y < 0.0 && south.x == 0.0);
+        assert_eq!(grid.gradient_at(5.0, 95.0), Vec2::default());
+    }
+}
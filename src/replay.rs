@@ -0,0 +1,217 @@
+use piston_window::*;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::being::BeingType;
+use crate::food::{Food, FoodType};
+use crate::simulation::Simulation;
+
+// File layout: MAGIC, the world width and height as f32, then one frame per tick.
+// A frame is a u32 being count followed by (x: f32, y: f32, size: f32, type: u8) per
+// being, then a u32 food count followed by (x: f32, y: f32, type: u8) per food.
+// Everything little-endian
+const MAGIC: &[u8; 5] = b"SLRP2";
+
+pub struct FrameBeing {
+    pub x: f32,
+    pub y: f32,
+    pub size: f32,
+    pub being_type: BeingType,
+}
+
+pub struct FrameFood {
+    pub x: f32,
+    pub y: f32,
+    pub food_type: FoodType,
+}
+
+pub struct Frame {
+    pub beings: Vec<FrameBeing>,
+    pub foods: Vec<FrameFood>,
+}
+
+pub struct Recording {
+    pub world_width: f64,
+    pub world_height: f64,
+    pub frames: Vec<Frame>,
+}
+
+impl Frame {
+    pub fn draw(&self, transform: math::Matrix2d, g: &mut G2d) {
+        for food in &self.foods {
+            let food = Food { x: food.x as f64, y: food.y as f64, energy: 0.0, food_type: food.food_type };
+            food.draw(transform, g);
+        }
+
+        for being in &self.beings {
+            let size = being.size as f64;
+            ellipse(being.being_type.color(), [being.x as f64, being.y as f64, size, size], transform, g);
+        }
+    }
+}
+
+// Appends a frame per recorded tick, opt-in with --record
+pub struct Recorder {
+    out: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, world_width: f64, world_height: f64) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(MAGIC)?;
+        out.write_all(&(world_width as f32).to_le_bytes())?;
+        out.write_all(&(world_height as f32).to_le_bytes())?;
+        Ok(Recorder { out })
+    }
+
+    pub fn record(&mut self, sim: &Simulation) -> io::Result<()> {
+        let out = &mut self.out;
+
+        out.write_all(&(sim.beings.len() as u32).to_le_bytes())?;
+        for being in &sim.beings {
+            out.write_all(&(being.x as f32).to_le_bytes())?;
+            out.write_all(&(being.y as f32).to_le_bytes())?;
+            out.write_all(&(being.size() as f32).to_le_bytes())?;
+            out.write_all(&[being_type_tag(being.being_type)])?;
+        }
+
+        out.write_all(&(sim.foods.len() as u32).to_le_bytes())?;
+        for food in &sim.foods {
+            out.write_all(&(food.x as f32).to_le_bytes())?;
+            out.write_all(&(food.y as f32).to_le_bytes())?;
+            out.write_all(&[food_type_tag(food.food_type)])?;
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+pub fn load(path: &Path) -> io::Result<Recording> {
+    let mut input = BufReader::new(File::open(path)?);
+
+    let mut magic = [0u8; 5];
+    input.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a replay file"));
+    }
+    let world_width = read_f32(&mut input)? as f64;
+    let world_height = read_f32(&mut input)? as f64;
+
+    let mut frames = Vec::new();
+    // A clean end of file can only happen between frames
+    while let Some(being_count) = read_count(&mut input)? {
+        let mut beings = Vec::with_capacity(being_count);
+        for _ in 0..being_count {
+            beings.push(FrameBeing {
+                x: read_f32(&mut input)?,
+                y: read_f32(&mut input)?,
+                size: read_f32(&mut input)?,
+                being_type: being_type_from_tag(read_u8(&mut input)?)?,
+            });
+        }
+
+        let food_count = read_count(&mut input)?.ok_or_else(|| invalid("truncated frame"))?;
+        let mut foods = Vec::with_capacity(food_count);
+        for _ in 0..food_count {
+            foods.push(FrameFood {
+                x: read_f32(&mut input)?,
+                y: read_f32(&mut input)?,
+                food_type: food_type_from_tag(read_u8(&mut input)?)?,
+            });
+        }
+
+        frames.push(Frame { beings, foods });
+    }
+
+    Ok(Recording { world_width, world_height, frames })
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+// None at end of file
+fn read_count(input: &mut impl Read) -> io::Result<Option<usize>> {
+    let mut buf = [0u8; 4];
+    match input.read_exact(&mut buf) {
+        Ok(()) => Ok(Some(u32::from_le_bytes(buf) as usize)),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn read_f32(input: &mut impl Read) -> io::Result<f32> {
+    let mut buf = [0u8; 4];
+    input.read_exact(&mut buf)?;
+    Ok(f32::from_le_bytes(buf))
+}
+
+fn read_u8(input: &mut impl Read) -> io::Result<u8> {
+    let mut buf = [0u8; 1];
+    input.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn being_type_tag(being_type: BeingType) -> u8 {
+    match being_type {
+        BeingType::Herbivore => 0,
+        BeingType::Carnivore => 1,
+        BeingType::Omnivore => 2,
+        BeingType::Scavenger => 3,
+        BeingType::Plant => 4,
+    }
+}
+
+fn being_type_from_tag(tag: u8) -> io::Result<BeingType> {
+    BeingType::ALL.get(tag as usize).copied().ok_or_else(|| invalid("unknown being type"))
+}
+
+fn food_type_tag(food_type: FoodType) -> u8 {
+    match food_type {
+        FoodType::Plant => 0,
+        FoodType::Fruit => 1,
+        FoodType::Carrion => 2,
+    }
+}
+
+fn food_type_from_tag(tag: u8) -> io::Result<FoodType> {
+    match tag {
+        0 => Ok(FoodType::Plant),
+        1 => Ok(FoodType::Fruit),
+        2 => Ok(FoodType::Carrion),
+        _ => Err(invalid("unknown food type")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn recorded_ticks_replay() {
+        let path = std::env::temp_dir().join(format!("simple-life-replay-{}.bin", std::process::id()));
+        let mut sim = Simulation::new(Config::default(), 1);
+        let first: Vec<(f32, f32)> = sim.beings.iter().map(|b| (b.x as f32, b.y as f32)).collect();
+
+        let mut recorder = Recorder::create(&path, sim.config.world_width, sim.config.world_height).unwrap();
+        for _ in 0..10 {
+            recorder.record(&sim).unwrap();
+            sim.tick();
+        }
+        recorder.finish().unwrap();
+        let recording = load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let recording = recording.unwrap();
+
+        assert_eq!(recording.frames.len(), 10);
+        assert_eq!(recording.world_width, sim.config.world_width);
+        let replayed: Vec<(f32, f32)> = recording.frames[0].beings.iter().map(|b| (b.x, b.y)).collect();
+        assert_eq!(replayed, first);
+    }
+}
@@ -0,0 +1,27 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+// SplitMix64 finalizer, spreads neighbouring inputs far apart
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Independent stream for one being on one tick, so the parallel update
+// stays reproducible no matter how rayon schedules the work
+pub fn sub_rng(seed: u64, index: usize, tick: u64) -> StdRng {
+    StdRng::seed_from_u64(mix(seed ^ mix(tick ^ mix(index as u64))))
+}
+
+// Stream for the world's biome layout, kept apart from the main generator so
+// switching biomes on or off doesn't change anything else about a seeded run
+pub fn biome_rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(mix(seed ^ 0xB10E))
+}
+
+// Seed for the index-th of several independent runs sharing one base seed
+pub fn derive_seed(seed: u64, index: usize) -> u64 {
+    mix(seed ^ mix(!(index as u64)))
+}
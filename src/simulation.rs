@@ -0,0 +1,1246 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::being::{Being, BeingType, UpdateOutcome, World};
+use crate::config::{BoundaryMode, Config, CullPolicy, FoodDistribution, InitialPopulation, ReproductionMode};
+use crate::corpse::Corpse;
+use crate::energy_audit::EnergyAudit;
+use crate::entity::Entity;
+use crate::environment::Environment;
+use crate::events::{DeathCause, SimEvent};
+use crate::food::Food;
+use crate::genetics::Genetics;
+use crate::pheromone::PheromoneGrid;
+use crate::rng;
+use crate::simulation_stats::{count_by_type, SimulationStats, StatsSnapshot, HISTORY_CAPACITY};
+use crate::spatial::{QuadTree, SpatialGrid};
+use crate::vec2::Vec2;
+
+pub const FOOD_GRID_CELL_SIZE: f64 = 30.0;  // Roughly the largest evolved perception
+const CARRION_SHARE: f32 = 0.5;  // Part of a rotted corpse's energy left as carrion
+const PHEROMONE_CELL_SIZE: f64 = 20.0;
+const PLACEMENT_ATTEMPTS: usize = 10;  // Random spots tried per initial being before settling for a wall
+
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    seed: u64,
+    tick: u64,
+    next_id: u64,
+    beings: &'a [Being],
+    foods: &'a [Food],
+    corpses: &'a [Corpse],
+    food_centers: &'a [(f64, f64)],
+    stats: &'a SimulationStats,
+}
+
+// One entry of a genome export
+#[derive(Serialize)]
+struct GenomeRef<'a> {
+    id: u64,
+    being_type: BeingType,
+    genetics: &'a Genetics,
+}
+
+#[derive(Deserialize)]
+struct Snapshot {
+    seed: u64,
+    tick: u64,
+    #[serde(default)]
+    next_id: u64,
+    beings: Vec<Being>,
+    foods: Vec<Food>,
+    #[serde(default)]
+    corpses: Vec<Corpse>,
+    #[serde(default)]
+    food_centers: Vec<(f64, f64)>,
+    stats: SimulationStats,
+}
+
+pub struct Simulation {
+    pub beings: Vec<Being>,
+    pub foods: Vec<Food>,
+    pub corpses: Vec<Corpse>,
+    pub food_centers: Vec<(f64, f64)>,  // Only used with clustered food
+    pub environment: Environment,  // Rebuilt from the seed, never saved
+    pub pheromones: PheromoneGrid,  // Starts empty again after loading
+    pub stats: SimulationStats,
+    pub config: Config,
+    seed: u64,
+    tick: u64,
+    next_id: u64,  // Next being id to hand out, ids start at 1
+    food_backlog: f64,  // Fraction of a food item owed with food_per_tick, starts at zero again after loading
+    rng: StdRng,
+}
+
+impl Simulation {
+    pub fn new(config: Config, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let beings = match config.initial {
+            Some(initial) => Simulation::random_population(&initial, &config, &mut rng),
+            None => Simulation::classic_population(&config, &mut rng),
+        };
+
+        let stats = SimulationStats {
+            energy_history: VecDeque::with_capacity(HISTORY_CAPACITY + 1),
+            population_history: VecDeque::with_capacity(HISTORY_CAPACITY + 1),
+            ..Default::default()
+        };
+
+        Simulation::from_state(config, seed, 0, beings, Vec::new(), stats, rng)
+    }
+
+    // A few beings of every type at fixed spots, placed relative to the world size
+    fn classic_population(config: &Config, rng: &mut StdRng) -> Vec<Being> {
+        let (w, h) = (config.world_width, config.world_height);
+        vec![
+            Simulation::initial_being(w / 3.0, h / 3.0, BeingType::Herbivore, config, rng),
+            Simulation::initial_being(w / 4.0, h / 4.0, BeingType::Herbivore, config, rng),
+            Simulation::initial_being(w * 2.0 / 3.0, h / 3.0, BeingType::Carnivore, config, rng),
+            Simulation::initial_being(w * 3.0 / 4.0, h / 4.0, BeingType::Carnivore, config, rng),
+            Simulation::initial_being(w / 2.0, h * 2.0 / 3.0, BeingType::Omnivore, config, rng),
+            Simulation::initial_being(w * 3.0 / 4.0, h * 3.0 / 4.0, BeingType::Scavenger, config, rng),
+            Simulation::initial_being(w / 4.0, h * 3.0 / 4.0, BeingType::Plant, config, rng),
+            Simulation::initial_being(w / 3.0, h * 2.0 / 3.0, BeingType::Plant, config, rng),
+            Simulation::initial_being(w * 2.0 / 3.0, h / 2.0, BeingType::Plant, config, rng),
+        ]
+    }
+
+    // Initial beings take their genes from the configured presets, if any
+    fn initial_being(x: f64, y: f64, being_type: BeingType, config: &Config, rng: &mut StdRng) -> Being {
+        let genetics = config.initial_genetics(being_type, rng);
+        Being::with_genetics(x, y, being_type, genetics, config)
+    }
+
+    // The requested number of each type, each at a random spot clear of the walls
+    fn random_population(initial: &InitialPopulation, config: &Config, rng: &mut StdRng) -> Vec<Being> {
+        let mut beings = Vec::new();
+
+        for being_type in BeingType::ALL {
+            for _ in 0..initial.get(being_type) {
+                let mut being = Simulation::initial_being(0.0, 0.0, being_type, config, rng);
+                let size = being.size();
+                for _ in 0..PLACEMENT_ATTEMPTS {
+                    being.x = rng.random_range(0.0..(config.world_width - size).max(1.0));
+                    being.y = rng.random_range(0.0..(config.world_height - size).max(1.0));
+                    if !config.walls.iter().any(|w| w.overlaps_square(being.x, being.y, size)) {
+                        break;
+                    }
+                }
+                being.home = (being.x, being.y);
+                beings.push(being);
+            }
+        }
+
+        beings
+    }
+
+    fn from_state(
+        config: Config,
+        seed: u64,
+        tick: u64,
+        beings: Vec<Being>,
+        foods: Vec<Food>,
+        stats: SimulationStats,
+        mut rng: StdRng,
+    ) -> Self {
+        let food_centers = (0..config.food_clusters)
+            .map(|_| (rng.random_range(0.0..config.world_width), rng.random_range(0.0..config.world_height)))
+            .collect();
+
+        let environment = Environment::new(&config, &mut rng::biome_rng(seed));
+        let pheromones = PheromoneGrid::new(config.world_width, config.world_height, PHEROMONE_CELL_SIZE);
+
+        let next_id = beings.iter().map(|b| b.id).max().unwrap_or(0) + 1;
+        let mut sim = Simulation {
+            beings,
+            foods,
+            corpses: Vec::new(),
+            food_centers,
+            environment,
+            pheromones,
+            stats,
+            config,
+            seed,
+            tick,
+            next_id,
+            food_backlog: 0.0,
+            rng,
+        };
+
+        // Fresh beings, and those from snapshots older than ids, still need one
+        for i in 0..sim.beings.len() {
+            if sim.beings[i].id == 0 {
+                sim.beings[i].id = sim.take_id();
+                sim.stats.record_birth(&sim.beings[i], tick);
+            }
+        }
+        sim
+    }
+
+    fn take_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    pub fn current_tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn snapshot_stats(&self) -> StatsSnapshot {
+        let avg_energy = if self.beings.is_empty() {
+            0.0
+        } else {
+            self.beings.iter().map(|b| b.energy).sum::<f32>() / self.beings.len() as f32
+        };
+
+        StatsSnapshot {
+            tick: self.tick,
+            population: self.beings.len(),
+            counts: count_by_type(&self.beings),
+            avg_energy,
+            total_births: self.stats.total_births,
+            total_deaths: self.stats.total_deaths,
+            food: self.foods.len(),
+        }
+    }
+
+    // Manual placement from the UI, food never goes into a wall
+    pub fn spawn_being(&mut self, x: f64, y: f64, being_type: BeingType) {
+        let mut being = Being::new(x, y, being_type, &self.config, &mut self.rng);
+        being.id = self.take_id();
+        self.stats.record_birth(&being, self.tick);
+        self.beings.push(being);
+    }
+
+    pub fn spawn_food(&mut self, x: f64, y: f64) {
+        if !self.config.walls.iter().any(|w| w.contains(x, y)) {
+            let food = Food::at(x, y, &mut self.rng);
+            self.foods.push(food);
+        }
+    }
+
+    // Food spawn chance swings around food_spawn_rate over a season cycle
+    // `base` adjusted for the season and for how many herbivores are grazing
+    fn food_rate(&self, base: f64) -> f64 {
+        let mut rate = base;
+        if self.config.season_length > 0 {
+            let phase = (self.tick % self.config.season_length) as f64 / self.config.season_length as f64;
+            rate *= 1.0 + self.config.season_amplitude * (phase * std::f64::consts::TAU).sin();
+        }
+
+        // Logistic feedback, the more herbivores graze the less grows back
+        if self.config.carrying_capacity > 0 {
+            let herbivores = self.beings.iter().filter(|b| b.being_type == BeingType::Herbivore).count();
+            rate *= (1.0 - herbivores as f64 / self.config.carrying_capacity as f64).max(0.0);
+        }
+
+        rate.max(0.0)
+    }
+
+    // Family tree of every being that was born, render it with `dot -Tsvg`
+    pub fn export_lineage_dot(&self, path: &Path) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.stats.write_lineage_dot(&mut out)?;
+        out.flush()
+    }
+
+    // Type and genes of every living being as a JSON array, for analysis after a run
+    pub fn export_genomes(&self, path: &Path) -> io::Result<()> {
+        let genomes: Vec<GenomeRef> = self.beings.iter()
+            .map(|b| GenomeRef { id: b.id, being_type: b.being_type, genetics: &b.genetics })
+            .collect();
+
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut out, &genomes)?;
+        out.flush()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let snapshot = SnapshotRef {
+            seed: self.seed,
+            tick: self.tick,
+            next_id: self.next_id,
+            beings: &self.beings,
+            foods: &self.foods,
+            corpses: &self.corpses,
+            food_centers: &self.food_centers,
+            stats: &self.stats,
+        };
+
+        serde_json::to_writer(BufWriter::new(File::create(path)?), &snapshot)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path, config: Config) -> io::Result<Simulation> {
+        let snapshot: Snapshot = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+        for being in &snapshot.beings {
+            if !config.in_world(being.x, being.y) {
+                return Err(invalid("being position is outside the world"));
+            }
+            if !being.energy.is_finite() {
+                return Err(invalid("being energy is not finite"));
+            }
+        }
+        for food in &snapshot.foods {
+            if !config.in_world(food.x, food.y) {
+                return Err(invalid("food position is outside the world"));
+            }
+            if !food.energy.is_finite() {
+                return Err(invalid("food energy is not finite"));
+            }
+        }
+
+        // The main RNG state isn't stored, so resume from a stream derived from the tick
+        let rng = rng::sub_rng(snapshot.seed, usize::MAX, snapshot.tick);
+        let mut sim = Simulation::from_state(
+            config,
+            snapshot.seed,
+            snapshot.tick,
+            snapshot.beings,
+            snapshot.foods,
+            snapshot.stats,
+            rng,
+        );
+        sim.next_id = sim.next_id.max(snapshot.next_id);
+        sim.corpses = snapshot.corpses;
+        if !snapshot.food_centers.is_empty() {
+            sim.food_centers = snapshot.food_centers;
+        }
+        Ok(sim)
+    }
+
+    // Energy stored in beings, food and corpses
+    pub fn total_energy(&self) -> f64 {
+        energy_of(&self.beings) + energy_of(&self.foods) + energy_of(&self.corpses)
+    }
+
+    // Fingerprint of the world for golden-master checks: every being in id order with
+    // rounded position, quantized energy, type and genes, then the food positions in
+    // sorted order. Hand-rolled FNV-1a so the value doesn't change with the Rust version
+    pub fn state_hash(&self) -> u64 {
+        let quantize = |v: f64, scale: f64| (v * scale).round() as i64 as u64;
+        let mut words = Vec::new();
+
+        let mut beings: Vec<&Being> = self.beings.iter().collect();
+        beings.sort_by_key(|b| b.id);
+        for b in beings {
+            let g = &b.genetics;
+            words.extend([b.id, quantize(b.x, 100.0), quantize(b.y, 100.0), quantize(b.energy as f64, 1e4)]);
+            words.push(BeingType::ALL.iter().position(|&t| t == b.being_type).unwrap_or(0) as u64);
+            words.extend([g.speed, g.size, g.reproduction_rate, g.perception, g.max_energy, g.metabolism, g.mutation_strength, g.diet_preference]
+                .map(|gene| quantize(gene as f64, 1e4)));
+        }
+
+        let mut foods: Vec<(u64, u64)> = self.foods.iter().map(|f| (quantize(f.x, 100.0), quantize(f.y, 100.0))).collect();
+        foods.sort_unstable();
+        words.extend(foods.into_iter().flat_map(|(x, y)| [x, y]));
+
+        words.iter()
+            .flat_map(|w| w.to_le_bytes())
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3))
+    }
+
+    pub fn tick(&mut self) -> Vec<SimEvent> {
+        let mut audit = EnergyAudit::begin(|| self.total_energy());
+        let mut events = Vec::new();
+        log::debug!("tick {}: {} beings, {} food", self.tick, self.beings.len(), self.foods.len());
+
+        // Track population history, types that just died out are reported once
+        let known_extinctions = self.stats.extinction_events.len();
+        self.stats.record_population(&self.beings);
+        events.extend(self.stats.extinction_events[known_extinctions..].iter()
+            .map(|&(_, being_type)| SimEvent::Extinction { being_type }));
+
+        // Spawn food, a steady amount per tick or a single piece by chance
+        if self.config.food_per_tick > 0.0 {
+            self.food_backlog += self.food_rate(self.config.food_per_tick);
+            while self.food_backlog >= 1.0 && self.foods.len() < self.config.max_food {
+                self.grow_food(&mut audit);
+                self.food_backlog -= 1.0;
+            }
+            // Food that didn't fit under max_food isn't saved up for later
+            self.food_backlog = self.food_backlog.fract();
+        } else {
+            let spawn_chance = self.food_rate(self.config.food_spawn_rate).min(1.0);
+            if self.foods.len() < self.config.max_food && self.rng.random_range(0.0..1.0) < spawn_chance {
+                self.grow_food(&mut audit);
+            }
+        }
+
+        if self.config.food_distribution == FoodDistribution::Clustered {
+            self.drift_food_centers();
+        }
+
+        if self.config.disease_outbreak_chance > 0.0 && !self.beings.is_empty()
+            && self.rng.random_bool(self.config.disease_outbreak_chance.min(1.0)) {
+            let index = self.rng.random_range(0..self.beings.len());
+            self.beings[index].infected = true;
+        }
+
+        let stats = &mut self.stats;
+
+        // Parallel being updates
+        let beings_copy = self.beings.clone();
+        let wrap = self.config.boundary_mode == BoundaryMode::Wrap;
+        let (width, height) = (self.config.world_width, self.config.world_height);
+        let food_grid = SpatialGrid::from_entities(&self.foods, FOOD_GRID_CELL_SIZE, width, height, wrap);
+        let neighbors = QuadTree::build(&beings_copy, wrap.then_some((width, height)));
+        let (seed, tick, config) = (self.seed, self.tick, &self.config);
+
+        // Each being draws from its own (seed, index, tick) stream, food and
+        // corpses are only read here and modified serially afterwards
+        let world = World {
+            neighbors: &neighbors,
+            foods: &self.foods,
+            food_grid: &food_grid,
+            corpses: &self.corpses,
+            environment: &self.environment,
+            pheromones: &self.pheromones,
+            tick,
+            config,
+        };
+        let mut updates: Vec<(Being, UpdateOutcome)> = self.beings.par_iter_mut()
+            .enumerate()
+            .map(|(i, being)| {
+                let mut being_rng = rng::sub_rng(seed, i, tick);
+                let outcome = being.update(&world, &mut being_rng);
+                (being.clone(), outcome)
+            })
+            .collect();
+        audit.record("upkeep", || -updates.iter().map(|(_, o)| o.upkeep as f64).sum::<f64>());
+        audit.record("births", || {
+            -(updates.iter().filter(|(_, o)| o.new_being.is_some()).count() as f64 * config.repro_cost as f64)
+        });
+
+        // Contested food goes to the being with the lowest id, the others come away
+        // empty. Each piece is eaten at most once and removed in a single pass
+        let mut claims: Vec<(usize, u64, usize)> = updates.iter()
+            .enumerate()
+            .flat_map(|(i, (being, outcome))| outcome.eaten_food.iter().map(move |&idx| (idx, being.id, i)))
+            .collect();
+        claims.sort_unstable();
+        claims.dedup_by_key(|&mut (idx, _, _)| idx);
+
+        let mut eaten_food = vec![false; self.foods.len()];
+        for &(idx, _, i) in &claims {
+            let (being, food) = (&mut updates[i].0, &self.foods[idx]);
+            let (before, amount) = (being.energy, being.food_value(food, config));
+            audit.record("forage bonus", || (amount - food.energy) as f64);
+            audit.record("digestion", || (being.meal_gain(amount, config) - amount) as f64);
+            being.eat(amount, config);
+            events.push(SimEvent::Ate { predator_id: being.id, energy: being.energy - before });
+            eaten_food[idx] = true;
+        }
+        stats.food_eaten += claims.len();
+        let mut eaten_food = eaten_food.into_iter();
+        self.foods.retain(|_| !eaten_food.next().unwrap_or(false));
+
+        // Beings that were caught or grazed are gone without a corpse. Contested prey
+        // goes to the hunter with the lowest id like food does, and a hunter that is
+        // caught itself in the same tick goes hungry
+        let mut hunted = vec![false; updates.len()];
+        for idx in updates.iter().filter_map(|(_, o)| o.eaten_being) {
+            hunted[idx] = true;
+        }
+        let mut hunts: Vec<(usize, u64, usize)> = updates.iter()
+            .enumerate()
+            .filter(|&(i, _)| !hunted[i])
+            .filter_map(|(i, (being, outcome))| outcome.eaten_being.map(|idx| (idx, being.id, i)))
+            .collect();
+        hunts.sort_unstable();
+        hunts.dedup_by_key(|&mut (idx, _, _)| idx);
+
+        let mut eaten = vec![false; updates.len()];
+        for &(idx, _, i) in &hunts {
+            eaten[idx] = true;
+            stats.total_deaths += 1;
+            let prey = updates[idx].0.clone();
+            let hunter = &mut updates[i].0;
+            let (before, amount) = (hunter.energy, hunter.prey_value(&prey, config));
+            audit.record("predation loss", || (amount - prey.energy) as f64);
+            audit.record("digestion", || (hunter.meal_gain(amount, config) - amount) as f64);
+            hunter.eat(amount, config);
+            events.push(SimEvent::Ate { predator_id: hunter.id, energy: hunter.energy - before });
+            events.push(SimEvent::Death { id: prey.id, cause: DeathCause::Eaten });
+            log::trace!("{} {} at ({:.1}, {:.1}) died: eaten", prey.being_type, prey.id, prey.x, prey.y);
+        }
+
+        // Corpses are claimed the same way, one scavenger gets the whole corpse
+        let mut meals: Vec<(usize, u64, usize)> = updates.iter()
+            .enumerate()
+            .filter(|&(i, _)| !eaten[i])
+            .filter_map(|(i, (being, outcome))| outcome.eaten_corpse.map(|idx| (idx, being.id, i)))
+            .collect();
+        meals.sort_unstable();
+        meals.dedup_by_key(|&mut (idx, _, _)| idx);
+
+        for &(idx, _, i) in meals.iter().rev() {
+            let (being, corpse) = (&mut updates[i].0, self.corpses.remove(idx));
+            let before = being.energy;
+            audit.record("digestion", || (being.meal_gain(corpse.energy, config) - corpse.energy) as f64);
+            being.eat(corpse.energy, config);
+            events.push(SimEvent::Ate { predator_id: being.id, energy: being.energy - before });
+        }
+
+        // Corpses rot away after a while, leaving carrion that omnivores can still eat
+        let mut carrion = Vec::new();
+        self.corpses.retain_mut(|c| {
+            c.ticks_left = c.ticks_left.saturating_sub(1);
+            if c.ticks_left == 0 {
+                let food = Food::carrion(c.x, c.y, c.energy * CARRION_SHARE);
+                audit.record("rotting", || food.energy as f64 - c.energy as f64);
+                carrion.push(food);
+            }
+            c.ticks_left > 0
+        });
+        self.foods.extend(carrion);
+
+        let diversity = stats.diversity(&self.beings);
+        stats.diversity_history.push_back(diversity);
+        stats.record_genetics(&self.beings);
+
+        // Track energy history
+        if !self.beings.is_empty() {
+            let avg_energy = self.beings.iter().map(|b| b.energy).sum::<f32>() / self.beings.len() as f32;
+            stats.energy_history.push_back(avg_energy);
+        }
+
+        // Update beings and track births/deaths, the dead leave a corpse behind
+        let mut beings = Vec::with_capacity(updates.len());
+        for (i, (being, outcome)) in updates.into_iter().enumerate() {
+            let child = outcome.new_being.map(|mut child| {
+                child.id = self.next_id;
+                self.next_id += 1;
+                stats.total_births += 1;
+                stats.record_birth(&child, tick);
+                events.push(SimEvent::Birth { id: child.id, parent_id: being.id });
+                log::trace!("{} {} born at ({:.1}, {:.1}) to {}", child.being_type, child.id, child.x, child.y, being.id);
+                child
+            });
+
+            for b in (!eaten[i]).then_some(being).into_iter().chain(child) {
+                if b.energy <= 0.0 || b.age > b.max_age {
+                    stats.total_deaths += 1;
+                    let cause = if b.energy <= 0.0 { DeathCause::Starved } else { DeathCause::OldAge };
+                    events.push(SimEvent::Death { id: b.id, cause });
+                    log::trace!("{} {} at ({:.1}, {:.1}) died: {:?}", b.being_type, b.id, b.x, b.y, cause);
+                    let corpse = Corpse::from_being(&b, config);
+                    audit.record("deaths", || corpse.energy as f64 - b.energy as f64);
+                    self.corpses.push(corpse);
+                } else {
+                    beings.push(b);
+                }
+            }
+        }
+        self.beings = beings;
+        if self.config.min_separation > 0.0 {
+            self.separate_beings();
+        }
+
+        // Herbivores mark where they walk while older trails fade
+        if self.config.pheromone_deposit > 0.0 {
+            self.pheromones.decay(self.config.pheromone_decay);
+            for b in self.beings.iter().filter(|b| b.being_type == BeingType::Herbivore) {
+                self.pheromones.deposit(b.x, b.y, self.config.pheromone_deposit);
+            }
+        }
+
+        if self.config.reproduction_mode == ReproductionMode::Sexual {
+            let children = self.mate_pairs(&mut events);
+            audit.record("mating", || -2.0 * children.len() as f64 * self.config.repro_cost as f64);
+            self.stats.total_births += children.len();
+            self.beings.extend(children);
+        }
+
+        // Enforce population limit
+        if self.beings.len() > self.config.max_beings {
+            audit.record("culled", || -energy_of(&self.beings));
+            let culled = self.cull();
+            log::trace!("culled {:?}", culled);
+            self.stats.total_deaths += culled.len();
+            events.extend(culled.into_iter().map(|id| SimEvent::Death { id, cause: DeathCause::Culled }));
+            audit.record("culled", || energy_of(&self.beings));
+        }
+
+        audit.check(self.tick, || self.total_energy());
+        self.tick += 1;
+
+        if self.config.epoch_length > 0 && self.tick.is_multiple_of(self.config.epoch_length) {
+            self.stats.close_epoch(self.tick);
+        }
+
+        // Keep history buffers manageable, dropping the oldest sample is O(1)
+        self.stats.trim_histories(HISTORY_CAPACITY);
+        events
+    }
+
+    fn grow_food(&mut self, audit: &mut EnergyAudit) {
+        let food = match self.config.food_distribution {
+            FoodDistribution::Uniform => Food::new(&self.config, &mut self.rng),
+            FoodDistribution::Clustered => Food::new_clustered(&self.food_centers, &self.config, &mut self.rng),
+        };
+        if let Some(food) = &food {
+            audit.record("food spawned", || food.energy as f64);
+        }
+        self.foods.extend(food);
+    }
+
+    pub fn infected_count(&self) -> usize {
+        self.beings.iter().filter(|b| b.infected).count()
+    }
+
+    // Resource centers wander slowly so herds have to follow them
+    fn drift_food_centers(&mut self) {
+        let drift = self.config.food_cluster_drift;
+        if drift <= 0.0 {
+            return;
+        }
+
+        for (x, y) in self.food_centers.iter_mut() {
+            *x = (*x + self.rng.random_range(-drift..drift)).clamp(0.0, self.config.world_width);
+            *y = (*y + self.rng.random_range(-drift..drift)).clamp(0.0, self.config.world_height);
+        }
+    }
+
+    // One relaxation pass that pushes overlapping beings apart, each taking half of the
+    // overlap. Coincident beings are split along the x axis, the lower id to the left
+    fn separate_beings(&mut self) {
+        let config = &self.config;
+        let factor = config.min_separation;
+        let max_size = self.beings.iter().map(|b| b.size()).fold(0.0, f64::max);
+        let wrap = (config.boundary_mode == BoundaryMode::Wrap).then_some((config.world_width, config.world_height));
+        let tree = QuadTree::build(&self.beings, wrap);
+
+        let mut pushes = vec![Vec2::default(); self.beings.len()];
+        for (i, a) in self.beings.iter().enumerate() {
+            let reach = (a.size() + max_size) / 2.0 * factor;
+            for (j, b) in tree.query_range((a.x, a.y), reach) {
+                if j <= i {
+                    continue;
+                }
+                let min_sep = (a.size() + b.size()) / 2.0 * factor;
+                let offset = a.offset_to(b.pos(), config);
+                let overlap = min_sep - offset.length();
+                if overlap <= 0.0 {
+                    continue;
+                }
+
+                let mut dir = offset.normalized();
+                if dir == Vec2::default() {
+                    dir = Vec2::new(if a.id < b.id { 1.0 } else { -1.0 }, 0.0);
+                }
+                pushes[i] += dir * (-overlap / 2.0);
+                pushes[j] += dir * (overlap / 2.0);
+            }
+        }
+
+        for (being, push) in self.beings.iter_mut().zip(pushes) {
+            if push != Vec2::default() {
+                being.push_by(push, config);
+            }
+        }
+    }
+
+    // Trims the population down to max_beings according to the configured policy,
+    // survivors keep their relative order. Returns the ids of the beings that had to go
+    fn cull(&mut self) -> Vec<u64> {
+        let max = self.config.max_beings;
+        let mut keep = vec![false; self.beings.len()];
+
+        match self.config.cull_policy {
+            CullPolicy::Tail => keep[..max].fill(true),
+            CullPolicy::LowestEnergy => {
+                let mut order: Vec<usize> = (0..self.beings.len()).collect();
+                order.sort_by(|&a, &b| self.beings[b].energy.total_cmp(&self.beings[a].energy));
+                for &idx in &order[..max] {
+                    keep[idx] = true;
+                }
+            },
+            CullPolicy::Random => {
+                for idx in rand::seq::index::sample(&mut self.rng, self.beings.len(), max) {
+                    keep[idx] = true;
+                }
+            },
+        }
+
+        let mut culled = Vec::new();
+        let mut keep = keep.into_iter();
+        self.beings.retain(|b| {
+            let kept = keep.next().unwrap_or(false);
+            if !kept {
+                culled.push(b.id);
+            }
+            kept
+        });
+        culled
+    }
+
+    // Pairs up willing beings of the same type that are close enough to each other
+    fn mate_pairs(&mut self, events: &mut Vec<SimEvent>) -> Vec<Being> {
+        let mut children = Vec::new();
+        let mut mated = vec![false; self.beings.len()];
+
+        for i in 0..self.beings.len() {
+            // Plants already budded during their own update
+            if mated[i]
+                || self.beings[i].reproduces_asexually(&self.config)
+                || !self.beings[i].can_replicate(&self.config, &mut self.rng)
+            {
+                continue;
+            }
+
+            let (head, tail) = self.beings.split_at_mut(i + 1);
+            let parent = &mut head[i];
+            let partner = tail.iter_mut().enumerate().find(|(j, b)| {
+                !mated[i + 1 + j]
+                    && b.being_type == parent.being_type
+                    && b.energy > self.config.min_repro_energy(b.being_type)
+                    && b.energy_after_birth(&self.config) >= self.config.repro_survival_floor
+                    && Being::mated_energy(parent, b) >= self.config.min_viable_energy
+                    && b.distance_sq_to(parent, &self.config) < self.config.mating_radius * self.config.mating_radius
+            });
+
+            if let Some((j, partner)) = partner {
+                let mut child = parent.mate(partner, &self.config, &mut self.rng);
+                child.id = self.next_id;
+                self.next_id += 1;
+                self.stats.record_birth(&child, self.tick);
+                events.push(SimEvent::Birth { id: child.id, parent_id: parent.id });
+                log::trace!("{} {} born at ({:.1}, {:.1}) to {} and {}", child.being_type, child.id, child.x, child.y, parent.id, partner.id);
+                children.push(child);
+                mated[i] = true;
+                mated[i + 1 + j] = true;
+            }
+        }
+
+        children
+    }
+}
+
+fn energy_of<T: Entity>(items: &[T]) -> f64 {
+    items.iter().map(|item| item.energy() as f64).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::food::FoodType;
+    use crate::simulation_stats::{BirthRecord, TypeCounts};
+    use crate::wall::Wall;
+
+    // No upkeep and no new food, energy only moves between beings, food and corpses
+    fn closed_config() -> Config {
+        Config { food_spawn_rate: 0.0, energy_decay: 0.0, ..Config::default() }
+    }
+
+    fn empty_simulation(config: Config) -> Simulation {
+        let mut sim = Simulation::new(config, 1);
+        sim.beings.clear();
+        sim.foods.clear();
+        sim.corpses.clear();
+        sim
+    }
+
+    fn add_being(sim: &mut Simulation, x: f64, y: f64, being_type: BeingType, energy: f32) -> u64 {
+        let mut being = Being::with_genetics(x, y, being_type, Genetics::plain(), &sim.config);
+        being.id = sim.take_id();
+        being.energy = energy;
+        let id = being.id;
+        sim.beings.push(being);
+        id
+    }
+
+    #[test]
+    fn shared_corpse_is_eaten_once() {
+        let mut sim = empty_simulation(closed_config());
+        let first = add_being(&mut sim, 400.0, 400.0, BeingType::Scavenger, 0.2);
+        add_being(&mut sim, 400.0, 400.0, BeingType::Scavenger, 0.2);
+        sim.corpses.push(Corpse { x: 403.0, y: 400.0, energy: 0.5, ticks_left: 100 });
+        let before = sim.total_energy();
+
+        let events = sim.tick();
+
+        assert!(sim.corpses.is_empty());
+        assert_eq!(events, vec![SimEvent::Ate { predator_id: first, energy: 0.5 }]);
+        assert!((sim.total_energy() - before).abs() < 1e-6);
+    }
+
+    #[test]
+    fn predation_only_loses_the_inefficient_fraction() {
+        let mut sim = empty_simulation(Config { allow_cannibalism: true, ..closed_config() });
+        let hunter = add_being(&mut sim, 400.0, 400.0, BeingType::Carnivore, 0.4);
+        let prey = add_being(&mut sim, 403.0, 400.0, BeingType::Carnivore, 0.3);
+        sim.beings[1].genetics.size = 0.8;
+        let before = sim.total_energy();
+
+        let events = sim.tick();
+
+        let lost = 0.3 * (1.0 - sim.config.carnivore_predation_efficiency) as f64;
+        assert!((before - sim.total_energy() - lost).abs() < 1e-6);
+        assert_eq!(events[1], SimEvent::Death { id: prey, cause: DeathCause::Eaten });
+        assert!(matches!(events[0], SimEvent::Ate { predator_id, .. } if predator_id == hunter));
+    }
+
+    #[test]
+    fn saved_run_with_births_loads_again() {
+        // A small world keeps most births close to an edge
+        let config = Config { world_width: 150.0, world_height: 150.0, ..Config::default() };
+        let mut sim = Simulation::new(config.clone(), 7);
+        for _ in 0..1500 {
+            sim.tick();
+            // Load refuses anything outside the world, so no tick may leave a being there
+            assert!(sim.beings.iter().all(|b| sim.config.in_world(b.x, b.y)), "tick {}", sim.current_tick());
+        }
+        assert!(sim.stats.total_births > 0);
+
+        let path = std::env::temp_dir().join(format!("simple-life-roundtrip-{}.json", std::process::id()));
+        sim.save(&path).expect("save");
+        let loaded = Simulation::load(&path, config);
+        let _ = std::fs::remove_file(&path);
+        let loaded = loaded.expect("load");
+
+        assert_eq!(loaded.current_tick(), sim.current_tick());
+        assert_eq!(loaded.beings.len(), sim.beings.len());
+        assert_eq!(loaded.state_hash(), sim.state_hash());
+    }
+
+    #[test]
+    fn culled_beings_count_as_deaths() {
+        let mut sim = empty_simulation(Config { max_beings: 2, ..closed_config() });
+        for i in 0..4 {
+            add_being(&mut sim, 100.0 + 100.0 * i as f64, 400.0, BeingType::Herbivore, 1.0);
+        }
+
+        let events = sim.tick();
+
+        let culled = events.iter().filter(|e| matches!(e, SimEvent::Death { cause: DeathCause::Culled, .. })).count();
+        assert_eq!(culled, 2);
+        assert_eq!(sim.stats.total_deaths, 2);
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_history() {
+        let run = |seed| {
+            let mut sim = Simulation::new(Config::default(), seed);
+            for _ in 0..500 {
+                sim.tick();
+            }
+            sim.stats.population_history
+        };
+
+        assert_eq!(run(3), run(3));
+        assert_ne!(run(3), run(4));
+    }
+
+    #[test]
+    fn population_stays_within_the_cap() {
+        let mut sim = Simulation::new(Config::default(), 1);
+        for _ in 0..100 {
+            sim.tick();
+            assert!(sim.beings.len() <= sim.config.max_beings, "tick {}", sim.current_tick());
+        }
+    }
+
+    #[test]
+    fn scavenger_eats_an_adjacent_corpse() {
+        let mut sim = empty_simulation(closed_config());
+        let scavenger = add_being(&mut sim, 400.0, 400.0, BeingType::Scavenger, 0.2);
+        sim.corpses.push(Corpse { x: 402.0, y: 400.0, energy: 0.4, ticks_left: 100 });
+
+        sim.tick();
+
+        assert!(sim.corpses.is_empty());
+        let being = sim.beings.iter().find(|b| b.id == scavenger).unwrap();
+        assert!(being.energy > 0.2, "energy {}", being.energy);
+    }
+
+    #[test]
+    fn extinction_is_logged_with_its_tick() {
+        let mut sim = empty_simulation(Config { food_spawn_rate: 0.0, ..Config::default() });
+        add_being(&mut sim, 400.0, 400.0, BeingType::Herbivore, 1e-6);
+        add_being(&mut sim, 100.0, 100.0, BeingType::Carnivore, 1.0);
+
+        for _ in 0..3 {
+            sim.tick();
+        }
+
+        // Starved during tick 0, so tick 1 is the first to count no herbivores
+        assert_eq!(sim.stats.extinction_events, vec![(1, BeingType::Herbivore)]);
+        assert_eq!(sim.stats.herbivore_history, VecDeque::from([1, 0, 0]));
+    }
+
+    #[test]
+    fn lowest_energy_cull_keeps_the_best_fed() {
+        let mut sim = empty_simulation(Config { max_beings: 3, cull_policy: CullPolicy::LowestEnergy, ..closed_config() });
+        let ids: Vec<u64> = [0.3, 0.9, 0.1, 0.7, 0.5].iter()
+            .enumerate()
+            .map(|(i, &energy)| add_being(&mut sim, 100.0 + 100.0 * i as f64, 400.0, BeingType::Herbivore, energy))
+            .collect();
+
+        sim.tick();
+
+        let mut survivors: Vec<u64> = sim.beings.iter().map(|b| b.id).collect();
+        survivors.sort_unstable();
+        assert_eq!(survivors, vec![ids[1], ids[3], ids[4]]);
+    }
+
+    #[test]
+    fn current_tick_counts_ticks() {
+        let mut sim = Simulation::new(Config::default(), 1);
+        for _ in 0..37 {
+            sim.tick();
+        }
+        assert_eq!(sim.current_tick(), 37);
+    }
+
+    #[test]
+    fn crowded_being_loses_more_energy() {
+        let mut sim = empty_simulation(Config { food_spawn_rate: 0.0, crowding_penalty: 0.00005, ..Config::default() });
+        let crowded = add_being(&mut sim, 200.0, 400.0, BeingType::Herbivore, 1.0);
+        for i in 0..10 {
+            add_being(&mut sim, 200.0 + 0.1 * i as f64, 400.1, BeingType::Herbivore, 1.0);
+        }
+        let isolated = add_being(&mut sim, 600.0, 400.0, BeingType::Herbivore, 1.0);
+
+        sim.tick();
+
+        let energy = |id| sim.beings.iter().find(|b| b.id == id).unwrap().energy;
+        assert!(energy(crowded) < energy(isolated), "crowded {} isolated {}", energy(crowded), energy(isolated));
+    }
+
+    #[test]
+    fn only_starving_carnivores_turn_cannibal() {
+        for (hunter_energy, hunts) in [(0.4, true), (1.0, false)] {
+            let mut sim = empty_simulation(Config { allow_cannibalism: true, ..closed_config() });
+            add_being(&mut sim, 400.0, 400.0, BeingType::Carnivore, hunter_energy);
+            let weaker = add_being(&mut sim, 403.0, 400.0, BeingType::Carnivore, 0.3);
+            sim.beings[1].genetics.size = 0.8;
+
+            let events = sim.tick();
+
+            let eaten = events.contains(&SimEvent::Death { id: weaker, cause: DeathCause::Eaten });
+            assert_eq!(eaten, hunts, "hunter energy {}", hunter_energy);
+        }
+    }
+
+    #[test]
+    fn wall_blocks_the_way_to_food() {
+        let wall = Wall { x: 400.0, y: 0.0, width: 10.0, height: 800.0 };
+        let mut sim = empty_simulation(Config { walls: vec![wall.clone()], ..closed_config() });
+        let id = add_being(&mut sim, 390.0, 400.0, BeingType::Herbivore, 1.0);
+        // Grown up and far-sighted, so it sees the food and heads for it
+        sim.beings[0].genetics.perception = 30.0;
+        sim.beings[0].age = 100;
+        sim.foods.push(Food { x: 415.0, y: 400.0, energy: 0.5, food_type: FoodType::Plant });
+
+        for _ in 0..30 {
+            sim.tick();
+            let being = sim.beings.iter().find(|b| b.id == id).unwrap();
+            assert!(being.x < wall.x && !wall.overlaps_square(being.x, being.y, being.size()), "tick {} at {}", sim.current_tick(), being.x);
+        }
+        assert_eq!(sim.foods.len(), 1);
+    }
+
+    #[test]
+    fn isolated_plant_grows_in_daylight() {
+        let mut sim = empty_simulation(Config { food_spawn_rate: 0.0, ..Config::default() });
+        add_being(&mut sim, 400.0, 400.0, BeingType::Plant, 0.5);
+
+        for _ in 0..50 {
+            let before = sim.beings[0].energy;
+            sim.tick();
+            assert!(sim.beings[0].energy > before, "tick {}", sim.current_tick());
+        }
+    }
+
+    #[test]
+    fn ids_are_unique_and_children_know_their_parent() {
+        let mut sim = empty_simulation(Config::default());
+        for i in 0..1000 {
+            sim.spawn_being((i % 40) as f64 * 20.0, (i / 40) as f64 * 20.0, BeingType::Herbivore);
+        }
+        let mut ids: Vec<u64> = sim.beings.iter().map(|b| b.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 1000);
+
+        // A smaller population is enough to see births
+        sim.beings.truncate(50);
+        for _ in 0..300 {
+            sim.tick();
+        }
+
+        let children: Vec<&BirthRecord> = sim.stats.births.iter().filter(|b| b.parent_id.is_some()).collect();
+        assert!(!children.is_empty());
+        for child in children {
+            let parent = child.parent_id.unwrap();
+            assert!(sim.stats.births.iter().any(|b| b.id == parent && b.birth_tick <= child.birth_tick), "child {} of {}", child.id, parent);
+        }
+    }
+
+    #[test]
+    fn herbivore_flees_instead_of_eating() {
+        let mut sim = empty_simulation(closed_config());
+        let herbivore = add_being(&mut sim, 400.0, 400.0, BeingType::Herbivore, 1.0);
+        // The carnivore is full, so it wanders instead of pouncing
+        add_being(&mut sim, 392.0, 400.0, BeingType::Carnivore, 2.0);
+        // Food lies toward the carnivore, running means leaving it behind
+        sim.foods.push(Food { x: 397.0, y: 400.0, energy: 0.5, food_type: FoodType::Plant });
+        sim.beings[0].genetics.size = 0.5;
+
+        sim.tick();
+
+        let being = sim.beings.iter().find(|b| b.id == herbivore).unwrap();
+        assert!(being.x > 400.0 && being.y == 400.0, "ended at ({}, {})", being.x, being.y);
+        assert_eq!(sim.foods.len(), 1);
+    }
+
+    #[test]
+    fn co_located_beings_stay_finite() {
+        for being_type in BeingType::ALL {
+            let mut sim = empty_simulation(Config { flocking_weight: 1.0, ..closed_config() });
+            for _ in 0..2 {
+                add_being(&mut sim, 400.0, 400.0, being_type, 1.0);
+            }
+            add_being(&mut sim, 400.0, 400.0, BeingType::Carnivore, 1.0);
+
+            sim.tick();
+
+            assert!(sim.beings.iter().all(|b| b.x.is_finite() && b.y.is_finite()), "{:?}", being_type);
+        }
+    }
+
+    #[test]
+    fn snapshot_sums_up_the_state() {
+        let mut sim = empty_simulation(closed_config());
+        add_being(&mut sim, 100.0, 100.0, BeingType::Herbivore, 0.5);
+        add_being(&mut sim, 200.0, 100.0, BeingType::Herbivore, 1.0);
+        add_being(&mut sim, 300.0, 100.0, BeingType::Carnivore, 1.5);
+        sim.foods.push(Food { x: 700.0, y: 500.0, energy: 0.5, food_type: FoodType::Plant });
+        sim.tick = 42;
+        sim.stats.total_births = 7;
+        sim.stats.total_deaths = 4;
+
+        assert_eq!(sim.snapshot_stats(), StatsSnapshot {
+            tick: 42,
+            population: 3,
+            counts: TypeCounts { herbivores: 2, carnivores: 1, ..Default::default() },
+            avg_energy: 1.0,
+            total_births: 7,
+            total_deaths: 4,
+            food: 1,
+        });
+    }
+
+    #[test]
+    fn initial_population_matches_the_config() {
+        let initial = InitialPopulation { herbivore: 20, carnivore: 5, omnivore: 8, scavenger: 2, plant: 0 };
+        let sim = Simulation::new(Config { initial: Some(initial), ..Config::default() }, 1);
+
+        assert_eq!(count_by_type(&sim.beings), TypeCounts { herbivores: 20, carnivores: 5, omnivores: 8, scavengers: 2, plants: 0 });
+        assert!(sim.beings.iter().all(|b| sim.config.in_world(b.x, b.y)));
+    }
+
+    #[test]
+    fn certain_transmission_infects_a_neighbor() {
+        for seed in 0..20 {
+            let mut sim = empty_simulation(Config { disease_transmission: 1.0, ..closed_config() });
+            sim.seed = seed;
+            add_being(&mut sim, 400.0, 400.0, BeingType::Herbivore, 1.0);
+            let healthy = add_being(&mut sim, 401.0, 400.0, BeingType::Herbivore, 1.0);
+            sim.beings[0].infected = true;
+
+            sim.tick();
+
+            assert!(sim.beings.iter().find(|b| b.id == healthy).unwrap().infected, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn eating_makes_a_being_flash() {
+        let mut sim = empty_simulation(closed_config());
+        add_being(&mut sim, 400.0, 400.0, BeingType::Herbivore, 1.0);
+        sim.foods.push(Food { x: 401.0, y: 400.0, energy: 0.5, food_type: FoodType::Plant });
+
+        sim.tick();
+
+        assert!(sim.foods.is_empty());
+        assert!(sim.beings[0].feed_flash > 0);
+    }
+
+    #[test]
+    fn contested_food_goes_to_one_being() {
+        let mut sim = empty_simulation(closed_config());
+        let first = add_being(&mut sim, 400.0, 400.0, BeingType::Herbivore, 1.0);
+        add_being(&mut sim, 402.0, 400.0, BeingType::Herbivore, 1.0);
+        sim.foods.push(Food { x: 401.0, y: 400.0, energy: 0.5, food_type: FoodType::Plant });
+
+        let events = sim.tick();
+
+        let meals: Vec<u64> = events.iter()
+            .filter_map(|e| match e { SimEvent::Ate { predator_id, .. } => Some(*predator_id), _ => None })
+            .collect();
+        assert_eq!(meals, vec![first]);
+        assert_eq!(sim.stats.food_eaten, 1);
+        assert!(sim.foods.is_empty());
+    }
+
+    #[test]
+    fn cold_regions_drain_more_energy() {
+        let mut sim = empty_simulation(Config { temperature_effect: 0.5, food_spawn_rate: 0.0, ..Config::default() });
+        let spots: Vec<(f64, f64)> = (0..16).flat_map(|i| (0..12).map(move |j| (i as f64 * 50.0, j as f64 * 50.0))).collect();
+        let temperature = |&(x, y): &(f64, f64)| sim.environment.temperature_at(x, y);
+        let cold = *spots.iter().min_by(|a, b| temperature(a).total_cmp(&temperature(b))).unwrap();
+        let warm = *spots.iter().max_by(|a, b| temperature(a).total_cmp(&temperature(b))).unwrap();
+        assert!(temperature(&cold) < temperature(&warm));
+
+        let cold_id = add_being(&mut sim, cold.0, cold.1, BeingType::Plant, 1.0);
+        let warm_id = add_being(&mut sim, warm.0, warm.1, BeingType::Plant, 1.0);
+        // Plants never move, and at midnight they get no sunlight to offset the decay
+        sim.tick = sim.config.day_length / 2;
+        sim.tick();
+
+        let energy = |id| sim.beings.iter().find(|b| b.id == id).unwrap().energy;
+        assert!(energy(cold_id) < energy(warm_id), "cold {} warm {}", energy(cold_id), energy(warm_id));
+    }
+
+    #[test]
+    fn exported_genomes_read_back() {
+        #[derive(Deserialize)]
+        struct Genome {
+            id: u64,
+            being_type: BeingType,
+            genetics: Genetics,
+        }
+
+        let mut sim = Simulation::new(Config::default(), 2);
+        for _ in 0..50 {
+            sim.tick();
+        }
+        let path = std::env::temp_dir().join(format!("simple-life-genomes-{}.json", std::process::id()));
+        sim.export_genomes(&path).unwrap();
+        let text = std::fs::read_to_string(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let genomes: Vec<Genome> = serde_json::from_str(&text.unwrap()).unwrap();
+        assert_eq!(genomes.len(), sim.beings.len());
+        for (genome, being) in genomes.iter().zip(&sim.beings) {
+            assert_eq!((genome.id, genome.being_type), (being.id, being.being_type));
+            assert!(genome.genetics == being.genetics, "being {}", being.id);
+        }
+    }
+
+    #[test]
+    fn food_grows_back_slower_under_heavy_grazing() {
+        let rate_with = |herbivores: usize| {
+            let mut sim = empty_simulation(Config { carrying_capacity: 100, season_length: 0, ..Config::default() });
+            for i in 0..herbivores {
+                add_being(&mut sim, (i % 20) as f64 * 30.0, (i / 20) as f64 * 30.0, BeingType::Herbivore, 1.0);
+            }
+            sim.food_rate(0.5)
+        };
+
+        assert!(rate_with(80) < rate_with(10));
+        assert_eq!(rate_with(0), 0.5);
+        assert_eq!(rate_with(100), 0.0);
+    }
+
+    #[test]
+    fn histories_stay_within_capacity() {
+        let mut sim = empty_simulation(closed_config());
+        for _ in 0..HISTORY_CAPACITY + 5 {
+            sim.tick();
+        }
+
+        assert_eq!(sim.stats.population_history.len(), HISTORY_CAPACITY);
+        assert_eq!(sim.stats.herbivore_history.len(), HISTORY_CAPACITY);
+        assert_eq!(sim.stats.avg_speed_history.len(), HISTORY_CAPACITY);
+    }
+
+    #[test]
+    fn state_hash_is_reproducible_and_sensitive() {
+        let run = || {
+            let mut sim = Simulation::new(Config::default(), 5);
+            for _ in 0..200 {
+                sim.tick();
+            }
+            sim
+        };
+        let (a, mut b) = (run(), run());
+        assert_eq!(a.state_hash(), b.state_hash());
+
+        // Vector order doesn't matter, only the beings themselves
+        b.beings.reverse();
+        assert_eq!(a.state_hash(), b.state_hash());
+
+        b.beings[0].energy += 0.1;
+        assert_ne!(a.state_hash(), b.state_hash());
+    }
+
+    #[test]
+    fn last_herbivore_eaten_reports_extinction() {
+        let mut sim = empty_simulation(closed_config());
+        let hunter = add_being(&mut sim, 400.0, 400.0, BeingType::Carnivore, 1.0);
+        let prey = add_being(&mut sim, 403.0, 400.0, BeingType::Herbivore, 0.5);
+        sim.beings[1].genetics.size = 0.5;
+
+        let mut events = sim.tick();
+        events.extend(sim.tick());
+
+        assert_eq!(events.len(), 3, "{:?}", events);
+        assert!(matches!(events[0], SimEvent::Ate { predator_id, energy } if predator_id == hunter && energy > 0.0));
+        assert_eq!(events[1..], [
+            SimEvent::Death { id: prey, cause: DeathCause::Eaten },
+            SimEvent::Extinction { being_type: BeingType::Herbivore },
+        ]);
+    }
+
+    #[test]
+    fn overlapping_beings_are_pushed_to_min_separation() {
+        let mut sim = empty_simulation(Config { min_separation: 1.0, ..closed_config() });
+        add_being(&mut sim, 400.0, 400.0, BeingType::Plant, 1.0);
+        add_being(&mut sim, 401.0, 400.0, BeingType::Plant, 1.0);
+        let min_sep = (sim.beings[0].size() + sim.beings[1].size()) / 2.0;
+
+        sim.separate_beings();
+
+        let gap = sim.beings[0].pos().distance_to(sim.beings[1].pos());
+        assert!(gap >= min_sep - 1e-9, "gap {} min_sep {}", gap, min_sep);
+        // Both moved by the same amount
+        assert!((400.0 - sim.beings[0].x - (sim.beings[1].x - 401.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn food_per_tick_spawns_a_fixed_amount() {
+        for (max_food, expected) in [(500, 200), (150, 150)] {
+            let mut sim = empty_simulation(Config { food_per_tick: 2.0, max_food, season_length: 0, carrying_capacity: 0, ..Config::default() });
+            for _ in 0..100 {
+                sim.tick();
+            }
+            assert_eq!(sim.foods.len(), expected, "max_food {}", max_food);
+        }
+    }
+
+    #[test]
+    fn edge_band_drains_energy() {
+        let mut sim = empty_simulation(Config { edge_margin: 20.0, edge_drain: 0.01, ..closed_config() });
+        let edge = add_being(&mut sim, 0.0, 300.0, BeingType::Plant, 1.0);
+        let center = add_being(&mut sim, 400.0, 300.0, BeingType::Plant, 1.0);
+
+        sim.tick();
+
+        let energy = |id| sim.beings.iter().find(|b| b.id == id).unwrap().energy;
+        assert!((energy(center) - energy(edge) - 0.01).abs() < 1e-6, "edge {} center {}", energy(edge), energy(center));
+    }
+}
@@ -1,9 +1,514 @@
-#[derive(Default)]
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::being::{Being, BeingType};
+use crate::genetics::{Genetics, PERCEPTION_RANGE, REPRODUCTION_RANGE, SIZE_RANGE, SPEED_RANGE};
+
+pub const ENERGY_SMOOTHING_WINDOW: usize = 20;
+pub const HISTORY_CAPACITY: usize = 1000;  // Ticks of history kept for the graph and the CSV export
+
+// Population per being type, counted in a single pass
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct TypeCounts {
+    pub herbivores: usize,
+    pub carnivores: usize,
+    pub omnivores: usize,
+    pub scavengers: usize,
+    pub plants: usize,
+}
+
+impl TypeCounts {
+    pub fn get(&self, being_type: BeingType) -> usize {
+        match being_type {
+            BeingType::Herbivore => self.herbivores,
+            BeingType::Carnivore => self.carnivores,
+            BeingType::Omnivore => self.omnivores,
+            BeingType::Scavenger => self.scavengers,
+            BeingType::Plant => self.plants,
+        }
+    }
+}
+
+pub fn count_by_type(beings: &[Being]) -> TypeCounts {
+    let mut counts = TypeCounts::default();
+    for being in beings {
+        match being.being_type {
+            BeingType::Herbivore => counts.herbivores += 1,
+            BeingType::Carnivore => counts.carnivores += 1,
+            BeingType::Omnivore => counts.omnivores += 1,
+            BeingType::Scavenger => counts.scavengers += 1,
+            BeingType::Plant => counts.plants += 1,
+        }
+    }
+    counts
+}
+
+// The current state in a few numbers, cheap enough to build and send every tick
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StatsSnapshot {
+    pub tick: u64,
+    pub population: usize,
+    #[serde(flatten)]
+    pub counts: TypeCounts,
+    pub avg_energy: f32,
+    pub total_births: usize,
+    pub total_deaths: usize,
+    pub food: usize,
+}
+
+// Births, deaths and meals over one stretch of epoch_length ticks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct EpochStats {
+    pub start_tick: u64,
+    pub end_tick: u64,
+    pub births: usize,
+    pub deaths: usize,
+    pub food_eaten: usize,
+}
+
+// One entry per being that ever lived, kept for the lineage export
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BirthRecord {
+    pub id: u64,
+    pub parent_id: Option<u64>,
+    pub being_type: BeingType,
+    pub birth_tick: u64,
+}
+
+#[derive(Default, Serialize, Deserialize)]
 pub struct SimulationStats {
     pub total_births: usize,
     pub total_deaths: usize,
     pub max_population: usize,
     pub food_eaten: usize,
-    pub energy_history: Vec<f32>,
-    pub population_history: Vec<usize>,
+    pub energy_history: VecDeque<f32>,
+    pub population_history: VecDeque<usize>,
+    #[serde(default)]
+    pub diversity_history: VecDeque<f32>,
+    // Population-average genes, one sample per tick and 0 while nobody is alive
+    #[serde(default)]
+    pub avg_speed_history: VecDeque<f32>,
+    #[serde(default)]
+    pub avg_size_history: VecDeque<f32>,
+    #[serde(default)]
+    pub avg_perception_history: VecDeque<f32>,
+    #[serde(default)]
+    pub avg_reproduction_history: VecDeque<f32>,
+    #[serde(default)]
+    pub herbivore_history: VecDeque<usize>,
+    #[serde(default)]
+    pub carnivore_history: VecDeque<usize>,
+    #[serde(default)]
+    pub omnivore_history: VecDeque<usize>,
+    #[serde(default)]
+    pub scavenger_history: VecDeque<usize>,
+    #[serde(default)]
+    pub plant_history: VecDeque<usize>,
+    #[serde(default)]
+    pub extinction_events: Vec<(usize, BeingType)>,  // Tick each type first died out
+    #[serde(default)]
+    pub ticks_recorded: usize,
+    #[serde(default)]
+    pub births: Vec<BirthRecord>,  // Never trimmed, the lineage needs every ancestor
+    #[serde(default)]
+    pub epochs: Vec<EpochStats>,
+    #[serde(default)]
+    pub epoch_start: EpochStats,  // Totals when the current epoch began
+}
+
+impl SimulationStats {
+    // Called once at the start of every tick with the current population
+    pub fn record_population(&mut self, beings: &[Being]) {
+        let tick = self.ticks_recorded;
+        self.ticks_recorded += 1;
+
+        self.population_history.push_back(beings.len());
+        if beings.len() > self.max_population {
+            self.max_population = beings.len();
+        }
+
+        let counts = count_by_type(beings);
+        for being_type in BeingType::ALL {
+            let n = counts.get(being_type);
+            let history = self.type_history_mut(being_type);
+            let was_alive = history.back().is_some_and(|&prev| prev > 0);
+            history.push_back(n);
+
+            if n == 0 && was_alive && !self.extinction_events.iter().any(|&(_, t)| t == being_type) {
+                self.extinction_events.push((tick, being_type));
+            }
+        }
+    }
+
+    pub fn record_birth(&mut self, being: &Being, tick: u64) {
+        self.births.push(BirthRecord {
+            id: being.id,
+            parent_id: being.parent_id,
+            being_type: being.being_type,
+            birth_tick: tick,
+        });
+    }
+
+    fn type_history_mut(&mut self, being_type: BeingType) -> &mut VecDeque<usize> {
+        match being_type {
+            BeingType::Herbivore => &mut self.herbivore_history,
+            BeingType::Carnivore => &mut self.carnivore_history,
+            BeingType::Omnivore => &mut self.omnivore_history,
+            BeingType::Scavenger => &mut self.scavenger_history,
+            BeingType::Plant => &mut self.plant_history,
+        }
+    }
+
+    // Clears every counter and history. The tick count keeps going so exports still
+    // line up with the simulation's ticks, and the births stay for the lineage
+    pub fn reset(&mut self) {
+        *self = SimulationStats {
+            ticks_recorded: self.ticks_recorded,
+            births: std::mem::take(&mut self.births),
+            epoch_start: EpochStats { start_tick: self.ticks_recorded as u64, ..Default::default() },
+            ..Default::default()
+        };
+    }
+
+    // Closes the running epoch at `tick` and starts the next one
+    pub fn close_epoch(&mut self, tick: u64) {
+        let start = self.epoch_start;
+        self.epochs.push(EpochStats {
+            start_tick: start.start_tick,
+            end_tick: tick,
+            births: self.total_births - start.births,
+            deaths: self.total_deaths - start.deaths,
+            food_eaten: self.food_eaten - start.food_eaten,
+        });
+        self.epoch_start = EpochStats {
+            start_tick: tick,
+            end_tick: tick,
+            births: self.total_births,
+            deaths: self.total_deaths,
+            food_eaten: self.food_eaten,
+        };
+    }
+
+    // Drops the oldest samples once a history grows past `capacity`
+    pub fn trim_histories(&mut self, capacity: usize) {
+        let trim_usize = |h: &mut VecDeque<usize>| while h.len() > capacity { h.pop_front(); };
+        let trim_f32 = |h: &mut VecDeque<f32>| while h.len() > capacity { h.pop_front(); };
+
+        trim_usize(&mut self.population_history);
+        trim_usize(&mut self.herbivore_history);
+        trim_usize(&mut self.carnivore_history);
+        trim_usize(&mut self.omnivore_history);
+        trim_usize(&mut self.scavenger_history);
+        trim_usize(&mut self.plant_history);
+        trim_f32(&mut self.energy_history);
+        trim_f32(&mut self.diversity_history);
+        trim_f32(&mut self.avg_speed_history);
+        trim_f32(&mut self.avg_size_history);
+        trim_f32(&mut self.avg_perception_history);
+        trim_f32(&mut self.avg_reproduction_history);
+    }
+
+    // Simple moving average of energy_history, the first samples average over
+    // however many values exist so far
+    pub fn smoothed_energy(&self, window: usize) -> Vec<f32> {
+        let window = window.max(1);
+        let mut sum = 0.0;
+
+        self.energy_history.iter().enumerate().map(|(i, &e)| {
+            sum += e;
+            if i >= window {
+                sum -= self.energy_history[i - window];
+            }
+            sum / (i + 1).min(window) as f32
+        }).collect()
+    }
+
+    pub fn record_genetics(&mut self, beings: &[Being]) {
+        let n = beings.len().max(1) as f32;
+        let mean = |gene: fn(&Genetics) -> f32| beings.iter().map(|b| gene(&b.genetics)).sum::<f32>() / n;
+
+        self.avg_speed_history.push_back(mean(|g| g.speed));
+        self.avg_size_history.push_back(mean(|g| g.size));
+        self.avg_perception_history.push_back(mean(|g| g.perception));
+        self.avg_reproduction_history.push_back(mean(|g| g.reproduction_rate));
+    }
+
+    // Mean variance of the main genes, each scaled to its clamp range first so
+    // no gene dominates. 0 means a genetically uniform population
+    pub fn diversity(&self, beings: &[Being]) -> f32 {
+        if beings.len() < 2 {
+            return 0.0;
+        }
+
+        let n = beings.len() as f32;
+        let variance = |gene: fn(&Genetics) -> f32, (min, max): (f32, f32)| {
+            let normalized = beings.iter().map(|b| gene(&b.genetics) / (max - min));
+            let mean = normalized.clone().sum::<f32>() / n;
+            normalized.map(|v| (v - mean).powi(2)).sum::<f32>() / n
+        };
+
+        (variance(|g| g.speed, SPEED_RANGE)
+            + variance(|g| g.size, SIZE_RANGE)
+            + variance(|g| g.reproduction_rate, REPRODUCTION_RANGE)
+            + variance(|g| g.perception, PERCEPTION_RANGE))
+            / 4.0
+    }
+
+    // Graphviz family tree, one node per recorded birth and an edge from each parent
+    pub fn write_lineage_dot(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "digraph lineage {{")?;
+        writeln!(out, "    node [shape=circle, style=filled, fontcolor=white];")?;
+
+        for birth in &self.births {
+            let [r, g, b, _] = birth.being_type.color().map(|c| (c * 255.0).round() as u8);
+            writeln!(
+                out,
+                "    n{} [label=\"{}\", fillcolor=\"#{:02x}{:02x}{:02x}\", tooltip=\"{:?} born at tick {}\"];",
+                birth.id, birth.id, r, g, b, birth.being_type, birth.birth_tick,
+            )?;
+        }
+
+        for birth in &self.births {
+            if let Some(parent) = birth.parent_id {
+                writeln!(out, "    n{} -> n{};", parent, birth.id)?;
+            }
+        }
+
+        writeln!(out, "}}")
+    }
+
+    pub fn export_csv(&self, path: &Path) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_csv(&mut out)?;
+        out.flush()
+    }
+
+    fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "tick,population,avg_energy,avg_energy_smoothed,herbivores,carnivores,omnivores,scavengers,plants,avg_speed,avg_size,avg_perception,avg_reproduction,extinctions")?;
+
+        // Energy is only recorded on ticks with a living population, so pair the
+        // newest energy samples with the non-empty ticks still in the window
+        let populated = self.population_history.iter().filter(|&&p| p > 0).count();
+        let skip = self.energy_history.len().saturating_sub(populated);
+        let missing = populated.saturating_sub(self.energy_history.len());
+        let smoothed = self.smoothed_energy(ENERGY_SMOOTHING_WINDOW);
+        let mut energy = self.energy_history.iter().zip(&smoothed).skip(skip);
+        let mut seen = 0;
+        let first_tick = self.ticks_recorded.saturating_sub(self.population_history.len());
+        let column = |history: &VecDeque<usize>, i: usize| history.get(i).map(|n| n.to_string()).unwrap_or_default();
+        // Gene histories may be younger than the population history after loading an
+        // older snapshot, so they line up with its newest entries
+        let populations = &self.population_history;
+        let gene_column = |history: &VecDeque<f32>, i: usize| {
+            let offset = populations.len().saturating_sub(history.len());
+            i.checked_sub(offset)
+                .filter(|_| populations[i] > 0)
+                .and_then(|j| history.get(j))
+                .map(|v| v.to_string())
+                .unwrap_or_default()
+        };
+
+        for (i, &population) in self.population_history.iter().enumerate() {
+            let tick = first_tick + i;
+            let avg_energy = if population > 0 {
+                seen += 1;
+                if seen > missing { energy.next() } else { None }
+            } else {
+                None
+            };
+
+            let extinctions: Vec<String> = self.extinction_events.iter()
+                .filter(|&&(t, _)| t == tick)
+                .map(|(_, being_type)| being_type.to_string())
+                .collect();
+
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                tick,
+                population,
+                avg_energy.map(|(e, _)| e.to_string()).unwrap_or_default(),
+                avg_energy.map(|(_, s)| s.to_string()).unwrap_or_default(),
+                column(&self.herbivore_history, i),
+                column(&self.carnivore_history, i),
+                column(&self.omnivore_history, i),
+                column(&self.scavenger_history, i),
+                column(&self.plant_history, i),
+                gene_column(&self.avg_speed_history, i),
+                gene_column(&self.avg_size_history, i),
+                gene_column(&self.avg_perception_history, i),
+                gene_column(&self.avg_reproduction_history, i),
+                extinctions.join(";"),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn beings(types: &[BeingType]) -> Vec<Being> {
+        let config = Config::default();
+        let mut rng = StdRng::seed_from_u64(1);
+        types.iter().map(|&t| Being::new(400.0, 400.0, t, &config, &mut rng)).collect()
+    }
+
+    #[test]
+    fn csv_lines_energy_up_with_populated_ticks() {
+        let stats = SimulationStats {
+            population_history: VecDeque::from([2, 0, 3]),
+            energy_history: VecDeque::from([1.0, 0.5]),
+            herbivore_history: VecDeque::from([2, 0, 3]),
+            avg_speed_history: VecDeque::from([1.5, 0.0, 2.0]),
+            extinction_events: vec![(1, BeingType::Herbivore)],
+            ticks_recorded: 3,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+
+        stats.write_csv(&mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), concat!(
+            "tick,population,avg_energy,avg_energy_smoothed,herbivores,carnivores,omnivores,scavengers,plants,avg_speed,avg_size,avg_perception,avg_reproduction,extinctions\n",
+            "0,2,1,1,2,,,,,1.5,,,,\n",
+            "1,0,,,0,,,,,,,,,herbivore\n",
+            "2,3,0.5,0.75,3,,,,,2,,,,\n",
+        ));
+    }
+
+    #[test]
+    fn varied_population_is_more_diverse() {
+        let mut uniform = beings(&[BeingType::Herbivore; 4]);
+        let genes = uniform[0].genetics.clone();
+        for being in &mut uniform {
+            being.genetics = genes.clone();
+        }
+        let mut varied = uniform.clone();
+        for (i, being) in varied.iter_mut().enumerate() {
+            being.genetics.speed = 0.5 + i as f32 * 0.8;
+            being.genetics.perception = 30.0 - i as f32 * 9.0;
+        }
+
+        let stats = SimulationStats::default();
+        assert_eq!(stats.diversity(&uniform), 0.0);
+        assert!(stats.diversity(&varied) > stats.diversity(&uniform));
+    }
+
+    #[test]
+    fn counts_a_mixed_population_by_type() {
+        use BeingType::*;
+        let population = beings(&[Herbivore, Carnivore, Herbivore, Plant, Omnivore, Herbivore, Plant]);
+
+        let counts = count_by_type(&population);
+
+        assert_eq!(counts, TypeCounts { herbivores: 3, carnivores: 1, omnivores: 1, scavengers: 0, plants: 2 });
+        assert_eq!(counts.get(Plant), 2);
+    }
+
+    #[test]
+    fn smoothing_ramps_up_then_slides() {
+        let stats = SimulationStats { energy_history: VecDeque::from([1.0, 2.0, 3.0, 4.0, 5.0]), ..Default::default() };
+
+        assert_eq!(stats.smoothed_energy(3), vec![1.0, 1.5, 2.0, 3.0, 4.0]);
+        assert_eq!(stats.smoothed_energy(0), vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn lineage_has_an_edge_per_child() {
+        let record = |id, parent_id| BirthRecord { id, parent_id, being_type: BeingType::Herbivore, birth_tick: id };
+        let stats = SimulationStats { births: vec![record(1, None), record(2, Some(1)), record(3, Some(1))], ..Default::default() };
+        let mut out = Vec::new();
+
+        stats.write_lineage_dot(&mut out).unwrap();
+
+        let dot = String::from_utf8(out).unwrap();
+        let edges: Vec<&str> = dot.lines().filter(|l| l.contains("->")).map(str::trim).collect();
+        assert_eq!(edges, vec!["n1 -> n2;", "n1 -> n3;"]);
+        assert!(dot.starts_with("digraph lineage {") && dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn records_average_genes() {
+        let mut population = beings(&[BeingType::Herbivore; 2]);
+        for (being, speed) in population.iter_mut().zip([1.0, 2.0]) {
+            being.genetics.speed = speed;
+            being.genetics.size = speed * 0.5;
+            being.genetics.perception = speed * 10.0;
+            being.genetics.reproduction_rate = 1.0;
+        }
+        let mut stats = SimulationStats::default();
+
+        stats.record_genetics(&population);
+        stats.record_genetics(&[]);
+
+        assert_eq!(stats.avg_speed_history, VecDeque::from([1.5, 0.0]));
+        assert_eq!(stats.avg_size_history, VecDeque::from([0.75, 0.0]));
+        assert_eq!(stats.avg_perception_history, VecDeque::from([15.0, 0.0]));
+        assert_eq!(stats.avg_reproduction_history, VecDeque::from([1.0, 0.0]));
+    }
+
+    #[test]
+    fn reset_clears_the_counters_and_histories() {
+        let mut stats = SimulationStats::default();
+        let population = beings(&[BeingType::Herbivore, BeingType::Carnivore]);
+        for _ in 0..5 {
+            stats.record_population(&population);
+            stats.record_genetics(&population);
+        }
+        stats.total_births = 4;
+        stats.total_deaths = 2;
+        stats.food_eaten = 9;
+
+        stats.reset();
+
+        assert_eq!(stats.ticks_recorded, 5);
+        assert_eq!((stats.total_births, stats.total_deaths, stats.food_eaten, stats.max_population), (0, 0, 0, 0));
+        assert!(stats.population_history.is_empty() && stats.herbivore_history.is_empty() && stats.avg_speed_history.is_empty());
+        assert!(stats.extinction_events.is_empty());
+        assert_eq!(stats.epoch_start.start_tick, 5);
+    }
+
+    #[test]
+    fn trimming_keeps_the_newest_in_order() {
+        let mut stats = SimulationStats {
+            population_history: (0..10).collect(),
+            energy_history: (0..10).map(|e| e as f32).collect(),
+            ..Default::default()
+        };
+
+        stats.trim_histories(4);
+
+        assert_eq!(stats.population_history, VecDeque::from([6, 7, 8, 9]));
+        assert_eq!(stats.energy_history, VecDeque::from([6.0, 7.0, 8.0, 9.0]));
+    }
+
+    #[test]
+    fn lineage_after_a_reset_still_has_every_parent() {
+        let mut parent = beings(&[BeingType::Herbivore]).remove(0);
+        parent.id = 1;
+        let mut child = parent.clone();
+        (child.id, child.parent_id) = (2, Some(1));
+        let mut stats = SimulationStats::default();
+        stats.record_birth(&parent, 0);
+
+        stats.reset();
+        stats.record_birth(&child, 5);
+
+        let mut out = Vec::new();
+        stats.write_lineage_dot(&mut out).unwrap();
+        let dot = String::from_utf8(out).unwrap();
+        assert!(dot.contains("n1 -> n2;"));
+        for parent_id in stats.births.iter().filter_map(|b| b.parent_id) {
+            assert!(dot.contains(&format!("    n{} [", parent_id)), "parent n{} has no node", parent_id);
+        }
+    }
 }
@@ -0,0 +1,307 @@
+use crate::being::Being;
+use crate::entity::Entity;
+use crate::vec2::Vec2;
+
+// Uniform bucket grid over the world, indices point back into the source slice
+pub struct SpatialGrid {
+    cell_size: f64,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+    points: Vec<(f64, f64)>,
+    width: f64,
+    height: f64,
+    wrap: bool,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f64, width: f64, height: f64, wrap: bool) -> Self {
+        let cols = (width / cell_size).ceil().max(1.0) as usize;
+        let rows = (height / cell_size).ceil().max(1.0) as usize;
+
+        SpatialGrid {
+            cell_size,
+            cols,
+            rows,
+            cells: vec![Vec::new(); cols * rows],
+            points: Vec::new(),
+            width,
+            height,
+            wrap,
+        }
+    }
+
+    pub fn from_entities<T: Entity>(items: &[T], cell_size: f64, width: f64, height: f64, wrap: bool) -> Self {
+        let mut grid = SpatialGrid::new(cell_size, width, height, wrap);
+        for item in items {
+            let (x, y) = item.position();
+            grid.insert(x, y);
+        }
+        grid
+    }
+
+    // Stores a point and returns its index (insertion order)
+    pub fn insert(&mut self, x: f64, y: f64) -> usize {
+        let index = self.points.len();
+        let (col, row) = self.cell_of(x, y);
+        self.cells[row * self.cols + col].push(index);
+        self.points.push((x, y));
+        index
+    }
+
+    fn cell_of(&self, x: f64, y: f64) -> (usize, usize) {
+        let col = (x / self.cell_size).floor().clamp(0.0, (self.cols - 1) as f64) as usize;
+        let row = (y / self.cell_size).floor().clamp(0.0, (self.rows - 1) as f64) as usize;
+        (col, row)
+    }
+
+    // Nearest stored point strictly closer than `range` whose index `predicate` allows,
+    // only scanning overlapping cells
+    pub fn nearest_within(&self, x: f64, y: f64, range: f64, predicate: impl Fn(usize) -> bool) -> Option<usize> {
+        if !self.wrap {
+            return self.nearest_in_cells(x, y, range, &predicate).map(|(idx, _)| idx);
+        }
+
+        // On a torus, also search from the query's mirror images across each seam
+        wrap_offsets(x, y, range, self.width, self.height)
+            .into_iter()
+            .filter_map(|(ox, oy)| self.nearest_in_cells(x + ox, y + oy, range, &predicate))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(idx, _)| idx)
+    }
+
+    fn nearest_in_cells(&self, x: f64, y: f64, range: f64, accept: &impl Fn(usize) -> bool) -> Option<(usize, f64)> {
+        let (min_col, min_row) = self.cell_of(x - range, y - range);
+        let (max_col, max_row) = self.cell_of(x + range, y + range);
+        let mut best: Option<(usize, f64)> = None;
+
+        for row in min_row..=max_row {
+            for col in min_col..=max_col {
+                for &idx in &self.cells[row * self.cols + col] {
+                    let (px, py) = self.points[idx];
+                    let dist_sq = Vec2::new(px, py).distance_sq(Vec2::new(x, y));
+                    if dist_sq < range * range && best.is_none_or(|(_, d)| dist_sq < d) && accept(idx) {
+                        best = Some((idx, dist_sq));
+                    }
+                }
+            }
+        }
+
+        best
+    }
+}
+
+// Shifts of a query circle that reach across the world edges, (0, 0) included
+fn wrap_offsets(x: f64, y: f64, range: f64, width: f64, height: f64) -> Vec<(f64, f64)> {
+    let shifts = |v: f64, extent: f64| {
+        let mut s = vec![0.0];
+        if v - range < 0.0 {
+            s.push(extent);
+        }
+        if v + range > extent {
+            s.push(-extent);
+        }
+        s
+    };
+
+    let xs = shifts(x, width);
+    let ys = shifts(y, height);
+    xs.iter().flat_map(|&ox| ys.iter().map(move |&oy| (ox, oy))).collect()
+}
+
+const QUADTREE_CAPACITY: usize = 8;
+const QUADTREE_MAX_DEPTH: usize = 10;
+
+struct Bounds {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+}
+
+impl Bounds {
+    fn intersects_circle(&self, cx: f64, cy: f64, radius: f64) -> bool {
+        let nearest = Vec2::new(cx.clamp(self.x, self.x + self.w), cy.clamp(self.y, self.y + self.h));
+        nearest.distance_to(Vec2::new(cx, cy)) <= radius
+    }
+
+    fn quadrant(&self, x: f64, y: f64) -> usize {
+        let right = x >= self.x + self.w / 2.0;
+        let bottom = y >= self.y + self.h / 2.0;
+        (bottom as usize) * 2 + right as usize
+    }
+
+    fn split(&self) -> [Bounds; 4] {
+        let (hw, hh) = (self.w / 2.0, self.h / 2.0);
+        [
+            Bounds { x: self.x, y: self.y, w: hw, h: hh },
+            Bounds { x: self.x + hw, y: self.y, w: hw, h: hh },
+            Bounds { x: self.x, y: self.y + hh, w: hw, h: hh },
+            Bounds { x: self.x + hw, y: self.y + hh, w: hw, h: hh },
+        ]
+    }
+}
+
+struct QuadNode {
+    bounds: Bounds,
+    items: Vec<usize>,
+    children: Option<Box<[QuadNode; 4]>>,
+}
+
+impl QuadNode {
+    fn new(bounds: Bounds) -> Self {
+        QuadNode { bounds, items: Vec::new(), children: None }
+    }
+
+    fn insert<T: Entity>(&mut self, items: &[T], idx: usize, depth: usize) {
+        let (x, y) = items[idx].position();
+
+        if let Some(children) = self.children.as_mut() {
+            children[self.bounds.quadrant(x, y)].insert(items, idx, depth + 1);
+            return;
+        }
+
+        self.items.push(idx);
+        if self.items.len() > QUADTREE_CAPACITY && depth < QUADTREE_MAX_DEPTH {
+            let [a, b, c, d] = self.bounds.split();
+            let mut children = Box::new([QuadNode::new(a), QuadNode::new(b), QuadNode::new(c), QuadNode::new(d)]);
+            for item in self.items.drain(..) {
+                let (x, y) = items[item].position();
+                children[self.bounds.quadrant(x, y)].insert(items, item, depth + 1);
+            }
+            self.children = Some(children);
+        }
+    }
+
+    fn query<'a, T: Entity>(&self, items: &'a [T], cx: f64, cy: f64, radius: f64, out: &mut Vec<(usize, &'a T)>) {
+        if !self.bounds.intersects_circle(cx, cy, radius) {
+            return;
+        }
+
+        for &idx in &self.items {
+            let item = &items[idx];
+            let (x, y) = item.position();
+            if Vec2::new(x, y).distance_sq(Vec2::new(cx, cy)) <= radius * radius {
+                out.push((idx, item));
+            }
+        }
+
+        if let Some(children) = self.children.as_ref() {
+            for child in children.iter() {
+                child.query(items, cx, cy, radius, out);
+            }
+        }
+    }
+}
+
+// Read-only snapshot of entity positions, rebuilt every tick before the parallel update
+pub struct QuadTree<'a, T = Being> {
+    items: &'a [T],
+    root: QuadNode,
+    wrap: Option<(f64, f64)>,
+}
+
+impl<'a, T: Entity> QuadTree<'a, T> {
+    // `wrap` holds the world size when the world is toroidal
+    pub fn build(items: &'a [T], wrap: Option<(f64, f64)>) -> Self {
+        // Size the root to fit every entity, newborns can sit slightly outside the world
+        let (mut min_x, mut min_y) = (f64::MAX, f64::MAX);
+        let (mut max_x, mut max_y) = (f64::MIN, f64::MIN);
+        for item in items {
+            let (x, y) = item.position();
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        if items.is_empty() {
+            (min_x, min_y, max_x, max_y) = (0.0, 0.0, 0.0, 0.0);
+        }
+
+        let bounds = Bounds { x: min_x, y: min_y, w: max_x - min_x, h: max_y - min_y };
+        let mut root = QuadNode::new(bounds);
+        for idx in 0..items.len() {
+            root.insert(items, idx, 0);
+        }
+
+        QuadTree { items, root, wrap }
+    }
+
+    // All entities whose position lies within `radius` of `center`, with their
+    // index in the slice the tree was built from
+    pub fn query_range(&self, center: (f64, f64), radius: f64) -> Vec<(usize, &'a T)> {
+        let mut out = Vec::new();
+        match self.wrap {
+            None => self.root.query(self.items, center.0, center.1, radius, &mut out),
+            Some((width, height)) => {
+                for (ox, oy) in wrap_offsets(center.0, center.1, radius, width, height) {
+                    self.root.query(self.items, center.0 + ox, center.1 + oy, radius, &mut out);
+                }
+            },
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Point(f64, f64);
+
+    impl Entity for Point {
+        fn position(&self) -> (f64, f64) {
+            (self.0, self.1)
+        }
+
+        fn energy(&self) -> f32 {
+            0.0
+        }
+    }
+
+    fn found(tree: &QuadTree<Point>, center: (f64, f64), radius: f64) -> Vec<usize> {
+        let mut found: Vec<usize> = tree.query_range(center, radius).into_iter().map(|(idx, _)| idx).collect();
+        found.sort_unstable();
+        found
+    }
+
+    #[test]
+    fn query_range_returns_exactly_the_points_in_the_circle() {
+        let mut points = vec![
+            Point(50.0, 50.0),  // 0, the center
+            Point(60.0, 50.0),  // 1, on the radius
+            Point(50.0, 39.9),  // 2, just outside
+            Point(57.0, 57.0),  // 3, inside on the diagonal
+            Point(58.0, 58.0),  // 4, outside on the diagonal
+            Point(10.0, 90.0),  // 5, far away
+        ];
+        // Enough filler in one corner to split the tree several levels deep
+        points.extend((0..40).map(|i| Point(90.0 + (i % 8) as f64, 90.0 + (i / 8) as f64)));
+        let tree = QuadTree::build(&points, None);
+
+        assert_eq!(found(&tree, (50.0, 50.0), 10.0), vec![0, 1, 3]);
+        assert_eq!(found(&tree, (10.0, 90.0), 0.5), vec![5]);
+        assert!(found(&tree, (30.0, 10.0), 5.0).is_empty());
+    }
+
+    #[test]
+    fn query_range_wraps_across_the_seam() {
+        let points = vec![Point(2.0, 50.0), Point(97.0, 50.0), Point(50.0, 50.0)];
+        let tree = QuadTree::build(&points, Some((100.0, 100.0)));
+
+        assert_eq!(found(&tree, (1.0, 50.0), 5.0), vec![0, 1]);
+        assert_eq!(found(&QuadTree::build(&points, None), (1.0, 50.0), 5.0), vec![0]);
+    }
+
+    #[test]
+    fn nearer_point_wins_in_a_large_world() {
+        // Both squared distances, scaled by 1000, saturate an i32 and would tie
+        let mut grid = SpatialGrid::new(5_000.0, 200_000.0, 200_000.0, false);
+        let far = grid.insert(100_000.0 + 90_000.0, 100_000.0);
+        let near = grid.insert(100_000.0 - 50_000.0, 100_000.0);
+        assert!(((50_000.0f64 * 50_000.0) * 1000.0) as i32 == ((90_000.0f64 * 90_000.0) * 1000.0) as i32);
+
+        assert_eq!(grid.nearest_within(100_000.0, 100_000.0, 95_000.0, |_| true), Some(near));
+        assert_eq!(grid.nearest_within(100_000.0, 100_000.0, 95_000.0, |idx| idx == far), Some(far));
+    }
+}
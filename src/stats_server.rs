@@ -0,0 +1,88 @@
+use std::io::{self, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread;
+use std::time::Duration;
+
+use crate::simulation_stats::StatsSnapshot;
+
+const QUEUE_LEN: usize = 64;  // Lines waiting for the sender thread, newer ones are dropped beyond this
+const WRITE_TIMEOUT: Duration = Duration::from_millis(200);  // A client this slow gets disconnected
+
+// Streams one JSON line per tick to every connected client, opt-in with --stats-port.
+// All socket work happens on a background thread, the simulation never waits on it
+pub struct StatsServer {
+    lines: SyncSender<String>,
+}
+
+impl StatsServer {
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        listener.set_nonblocking(true)?;
+
+        let (lines, queue) = mpsc::sync_channel(QUEUE_LEN);
+        thread::spawn(move || serve(listener, queue));
+        Ok(StatsServer { lines })
+    }
+
+    pub fn publish(&self, snapshot: &StatsSnapshot) {
+        let Ok(mut line) = serde_json::to_string(snapshot) else {
+            return;
+        };
+        line.push('\n');
+
+        // A full queue means the clients can't keep up, skip this tick for them
+        let _ = self.lines.try_send(line);
+    }
+}
+
+// Runs until the simulation drops its StatsServer
+fn serve(listener: TcpListener, queue: Receiver<String>) {
+    let mut clients: Vec<TcpStream> = Vec::new();
+
+    for line in queue {
+        while let Ok((client, _)) = listener.accept() {
+            let ready = client.set_nonblocking(false).and_then(|()| client.set_write_timeout(Some(WRITE_TIMEOUT)));
+            if ready.is_ok() {
+                clients.push(client);
+            }
+        }
+
+        // Disconnected or stalled clients are dropped
+        clients.retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::simulation::Simulation;
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn client_receives_json_lines() {
+        // Let the OS pick a free port, then hand it to the server
+        let port = TcpListener::bind(("127.0.0.1", 0)).unwrap().local_addr().unwrap().port();
+        let server = StatsServer::bind(port).unwrap();
+        let client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+
+        // Clients are picked up between lines, so keep publishing until one arrives
+        let mut sim = Simulation::new(Config::default(), 1);
+        let publisher = thread::spawn(move || {
+            for _ in 0..25 {
+                sim.tick();
+                server.publish(&sim.snapshot_stats());
+                thread::sleep(Duration::from_millis(20));
+            }
+        });
+
+        let mut line = String::new();
+        BufReader::new(client).read_line(&mut line).unwrap();
+        let snapshot: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert!(snapshot["tick"].as_u64().is_some_and(|tick| tick > 0));
+        assert!(snapshot["population"].is_u64());
+        publisher.join().unwrap();
+    }
+}
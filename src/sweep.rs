@@ -0,0 +1,41 @@
+use rayon::prelude::*;
+
+use crate::config::Config;
+use crate::rng;
+use crate::simulation::Simulation;
+use crate::simulation_stats::StatsSnapshot;
+
+// Runs one headless simulation per config side by side and returns their final
+// stats in config order. Each run gets its own seed derived from `seed` and its index
+pub fn run_sweep(configs: Vec<Config>, ticks: u64, seed: u64) -> Vec<StatsSnapshot> {
+    configs.into_par_iter()
+        .enumerate()
+        .map(|(i, config)| {
+            let mut sim = Simulation::new(config, rng::derive_seed(seed, i));
+            for _ in 0..ticks {
+                sim.tick();
+            }
+            sim.snapshot_stats()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::InitialPopulation;
+
+    #[test]
+    fn results_come_back_in_config_order() {
+        let only_plants = |plant| Config {
+            initial: Some(InitialPopulation { herbivore: 0, carnivore: 0, omnivore: 0, scavenger: 0, plant }),
+            ..Config::default()
+        };
+
+        let results = run_sweep(vec![only_plants(3), only_plants(7)], 1, 1);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!((results[0].counts.plants, results[1].counts.plants), (3, 7));
+        assert!(results.iter().all(|r| r.tick == 1));
+    }
+}
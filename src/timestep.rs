@@ -0,0 +1,89 @@
+// Classic fixed-timestep accumulator: real time goes in, a whole number of simulation
+// ticks comes out, so the dynamics don't depend on how fast the machine renders
+pub struct FixedTimestep {
+    step: f64,  // Seconds per tick
+    max_catch_up: u32,  // Most ticks a single frame may run
+    accumulator: f64,
+}
+
+impl FixedTimestep {
+    pub fn new(ticks_per_second: u64, max_catch_up: u32) -> Self {
+        FixedTimestep { step: 1.0 / ticks_per_second as f64, max_catch_up, accumulator: 0.0 }
+    }
+
+    // Ticks due after `elapsed` more seconds. A frame that took far too long only runs
+    // max_catch_up ticks and the rest of the backlog is dropped, otherwise a slow machine
+    // would fall further behind every frame
+    pub fn advance(&mut self, elapsed: f64) -> u32 {
+        if elapsed.is_finite() && elapsed > 0.0 {
+            self.accumulator += elapsed;
+        }
+
+        let due = (self.accumulator / self.step).floor();
+        if due >= self.max_catch_up as f64 {
+            self.accumulator = 0.0;
+            return self.max_catch_up;
+        }
+
+        self.accumulator -= due * self.step;
+        due as u32
+    }
+
+    // Forgets time that passed while the simulation wasn't supposed to run
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::simulation::Simulation;
+
+    // How much the world's energy changes over one second of frames at `fps`, without
+    // new food spawning at random
+    fn energy_change_in_a_second(fps: u32) -> f64 {
+        let mut sim = Simulation::new(Config { food_spawn_rate: 0.0, ..Config::default() }, 1);
+        let before = sim.total_energy();
+        let mut timestep = FixedTimestep::new(60, 8);
+        for _ in 0..fps {
+            for _ in 0..timestep.advance(1.0 / fps as f64) {
+                sim.tick();
+            }
+        }
+        assert_eq!(sim.current_tick(), 60, "{} fps", fps);
+        sim.total_energy() - before
+    }
+
+    #[test]
+    fn energy_change_does_not_depend_on_frame_rate() {
+        let slow = energy_change_in_a_second(30);
+        let fast = energy_change_in_a_second(120);
+
+        assert!(slow != 0.0);
+        assert!((slow - fast).abs() < 1e-9, "30 fps changed by {}, 120 fps by {}", slow, fast);
+    }
+
+    #[test]
+    fn slow_frames_are_capped() {
+        let mut timestep = FixedTimestep::new(60, 8);
+        assert_eq!(timestep.advance(1.0), 8);
+        assert_eq!(timestep.advance(0.0), 0);
+    }
+
+    #[test]
+    fn leftover_time_carries_over() {
+        let mut timestep = FixedTimestep::new(60, 8);
+
+        assert_eq!(timestep.advance(0.01), 0);
+        assert_eq!(timestep.advance(0.01), 1);
+        assert_eq!(timestep.advance(0.05), 3);
+        assert_eq!(timestep.advance(f64::NAN), 0);
+        assert_eq!(timestep.advance(-1.0), 0);
+
+        timestep.advance(0.016);
+        timestep.reset();
+        assert_eq!(timestep.advance(0.01), 0);
+    }
+}
@@ -0,0 +1,112 @@
+use std::ops::{Add, AddAssign, Mul, Sub};
+
+const MIN_LENGTH: f64 = 1e-9;  // Shorter vectors have no usable direction
+
+// Plain 2D vector for positions, offsets and headings
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Vec2 {
+    pub const fn new(x: f64, y: f64) -> Self {
+        Vec2 { x, y }
+    }
+
+    pub fn length_sq(self) -> f64 {
+        self.x * self.x + self.y * self.y
+    }
+
+    pub fn length(self) -> f64 {
+        self.length_sq().sqrt()
+    }
+
+    pub fn distance_sq(self, other: Vec2) -> f64 {
+        (other - self).length_sq()
+    }
+
+    pub fn distance_to(self, other: Vec2) -> f64 {
+        self.distance_sq(other).sqrt()
+    }
+
+    // Unit vector pointing from here to `target`, zero when they coincide
+    pub fn normalized_toward(self, target: Vec2) -> Vec2 {
+        (target - self).normalized()
+    }
+
+    // Unit vector in the same direction. Coincident points, or anything that isn't
+    // finite, give a zero vector instead of NaN so callers simply don't move
+    pub fn normalized(self) -> Vec2 {
+        let length = self.length();
+        if length.is_finite() && length > MIN_LENGTH {
+            Vec2::new(self.x / length, self.y / length)
+        } else {
+            Vec2::default()
+        }
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, other: Vec2) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, scale: f64) -> Vec2 {
+        Vec2::new(self.x * scale, self.y * scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let mut v = Vec2::new(1.0, 2.0) + Vec2::new(3.0, 4.0);
+        assert_eq!(v, Vec2::new(4.0, 6.0));
+        v += Vec2::new(-1.0, -1.0);
+        assert_eq!(v - Vec2::new(1.0, 1.0), Vec2::new(2.0, 4.0));
+        assert_eq!(v * 2.0, Vec2::new(6.0, 10.0));
+    }
+
+    #[test]
+    fn lengths_and_distances() {
+        let (a, b) = (Vec2::new(1.0, 1.0), Vec2::new(4.0, 5.0));
+        assert_eq!(Vec2::new(3.0, 4.0).length_sq(), 25.0);
+        assert_eq!(Vec2::new(3.0, 4.0).length(), 5.0);
+        assert_eq!(a.distance_sq(b), 25.0);
+        assert_eq!(a.distance_to(b), 5.0);
+        assert_eq!(b.distance_to(a), 5.0);
+    }
+
+    #[test]
+    fn directions() {
+        assert_eq!(Vec2::new(0.0, -2.0).normalized(), Vec2::new(0.0, -1.0));
+        assert_eq!(Vec2::new(1.0, 1.0).normalized_toward(Vec2::new(4.0, 5.0)), Vec2::new(0.6, 0.8));
+        // Coincident points and non-finite input have no direction
+        assert_eq!(Vec2::new(2.0, 2.0).normalized_toward(Vec2::new(2.0, 2.0)), Vec2::default());
+        assert_eq!(Vec2::new(f64::NAN, 1.0).normalized(), Vec2::default());
+    }
+}
@@ -0,0 +1,31 @@
+use piston_window::*;
+use serde::{Deserialize, Serialize};
+
+// Axis-aligned obstacle, beings can't pass through and food never spawns inside
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Wall {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Wall {
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    // Whether a square of side `size` with its corner at (x, y) overlaps the wall
+    pub fn overlaps_square(&self, x: f64, y: f64, size: f64) -> bool {
+        x < self.x + self.width && x + size > self.x && y < self.y + self.height && y + size > self.y
+    }
+
+    pub fn draw(&self, transform: math::Matrix2d, g: &mut G2d) {
+        rectangle(
+            [0.4, 0.4, 0.45, 1.0],  // Slate gray, darker than corpses
+            [self.x, self.y, self.width, self.height],
+            transform,
+            g,
+        );
+    }
+}